@@ -5,28 +5,76 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::thread;
 use std::sync::mpsc;
+use std::thread::JoinHandle;
 
 use eframe::egui;
-use egui::plot::{Line, Plot, PlotPoints};
+use egui::plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints, Points};
 use egui::{FontId, TextStyle};
 use egui::FontFamily::Proportional;
 
-use super::load_dataset;
-use super::model::{GAModelTracker, GAModel, Gene};
+use std::path::{Path, PathBuf};
+
+use super::{load_dataset, load_problem_file, validate_dataset, normalize_shares};
+use super::dataset::expected_share_count;
+use super::export::export;
+use super::model::{GAModel, TrackerUpdate};
+use super::problem::PropertyDistribution;
+use super::selection::SelectionKind;
+use super::crossover::CrossoverKind;
+use super::mutation::{MutationKind, MutationSchedule};
+use super::diversity::DiversityMetric;
+use super::nsga::ObjectiveMode;
+use super::metric::FitnessMetric;
+use super::constraint::{ConstraintHandling, Constraints};
+use super::problem::Problem;
+use super::island::{IslandModel, IslandParameters, Topology, Replacement};
+use super::checkpoint::Checkpoint;
+use super::control::Command;
+use super::log::{LogEntry, LogLevel, LogObserver, save_logs};
 
 pub struct GeneApp {
     pub model: GAModel,
-    pub sender: mpsc::Sender<bool>,
-    pub receiver: mpsc::Receiver<GAModelTracker>,
+    pub sender: mpsc::Sender<Command>,
+    pub receiver: mpsc::Receiver<TrackerUpdate>,
+    pub handler: Option<JoinHandle<()>>,
+    pub log_receiver: mpsc::Receiver<LogEntry>,
+    pub logs: Vec<LogEntry>,
+    // Lowest level shown in the Logs window
+    pub log_level: LogLevel,
+    pub log_search: String,
+    pub log_auto_scroll: bool,
+    pub log_file_path: String,
+    pub log_status: String,
     pub generation: usize,
+    pub step_generations: usize,
+    pub island_parameters: IslandParameters,
+    pub problem_file_path: String,
+    // Problem file whose constraint settings were put in the Constraints row
+    pub constraints_file_path: String,
     pub divide_file_path: String,
     pub properties_file_path: String,
+    pub heir_count: usize,
+    pub normalize_shares: bool,
+    pub dataset_warnings: Vec<String>,
+    pub export_file_path: String,
+    pub export_status: String,
+    pub checkpoint_file_path: String,
+    pub save_checkpoints: bool,
+    pub start_error: Option<String>,
     pub control_window: bool,
     pub logs_window: bool,
     pub plot_window: bool,
     pub fit_results_window: bool,
+    pub pareto_window: bool,
+    // Objectives on the axes of the Pareto front plot
+    pub pareto_x: usize,
+    pub pareto_y: usize,
+    // Down-sampled plot lines, rebuilt when the tracker changes
+    pub plot_lines: Vec<(String, Vec<[f64; 2]>)>,
+    pub plot_outdated: bool,
+    // Heir whose properties are listed in the Fit Result window
+    pub selected_heir: Option<usize>,
 }
 
 impl Default for GeneApp {
@@ -35,24 +83,52 @@ impl Default for GeneApp {
             model: GAModel::default(),
             sender: mpsc::channel().0,
             receiver: mpsc::channel().1,
+            handler: None,
+            log_receiver: mpsc::channel().1,
+            logs: Vec::new(),
+            log_level: LogLevel::Info,
+            log_search: String::new(),
+            log_auto_scroll: true,
+            log_file_path: "./logs.txt".to_string(),
+            log_status: String::new(),
             generation: 5000,
+            step_generations: 1,
+            island_parameters: IslandParameters::default(),
+            problem_file_path: String::new(),
+            constraints_file_path: String::new(),
             divide_file_path: "./property/divide10.txt".to_string(),
             properties_file_path: "./property/properties100.txt".to_string(),
+            heir_count: 0,
+            normalize_shares: false,
+            dataset_warnings: Vec::new(),
+            export_file_path: "./results.json".to_string(),
+            export_status: String::new(),
+            checkpoint_file_path: "./checkpoint.json".to_string(),
+            save_checkpoints: false,
+            start_error: None,
             control_window: true,
             logs_window: false,
             plot_window: false,
             fit_results_window: false,
+            pareto_window: false,
+            pareto_x: 0,
+            pareto_y: 1,
+            plot_lines: Vec::new(),
+            plot_outdated: true,
+            selected_heir: None,
         }
     }
 }
 
 impl eframe::App for GeneApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        match self.receiver.try_recv() {
-          Ok(tracker) => { self.model.tracker = tracker; }
-          Err(err) => {}
+        for update in self.receiver.try_iter() {
+            self.model.tracker.apply(update);
+            self.plot_outdated = true;
         }
 
+        self.logs.extend(self.log_receiver.try_iter());
+
         let mut style = (*ctx.style()).clone();
 
         style.text_styles = [
@@ -81,6 +157,10 @@ impl eframe::App for GeneApp {
                     self.fit_results_window = !self.fit_results_window;
                 }
 
+                if ui.button("Pareto front").clicked() {
+                    self.pareto_window = !self.pareto_window;
+                }
+
                 if ui.button("Logs").clicked() {
                     self.logs_window = !self.logs_window;
                 }
@@ -88,6 +168,9 @@ impl eframe::App for GeneApp {
             ui.add_space(5.0);
         });
 
+        // Only the mutation and selection parameters can change during a run
+        let locked = self.model.tracker.is_running;
+
         egui::Window::new("Control")
           .default_size(egui::Vec2::new(1000.0, 300.0))
           .open(&mut self.control_window)
@@ -98,24 +181,61 @@ impl eframe::App for GeneApp {
               ui.add_space(15.0);
 
               ui.collapsing("Dataset", |ui| {
+                  ui.set_enabled(!locked);
+
                   // Dataset
+                  ui.label("Problem File Path (.toml or .json, empty: divide and properties files)");
+                  ui.text_edit_singleline(&mut self.problem_file_path);
+                  ui.add_space(10.0);
+
                   ui.label("Divide File Path");
                   ui.text_edit_singleline(&mut self.divide_file_path);
                   ui.add_space(10.0);
 
                   ui.label("Properties File Path");
                   ui.text_edit_singleline(&mut self.properties_file_path);
+                  ui.add_space(10.0);
+
+                  egui::Grid::new("dataset_grid").show(ui, |ui| {
+                      ui.label("Heir Count (0: from divide file name)");
+                      ui.add(egui::DragValue::new(&mut self.heir_count));
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Normalize Shares");
+                      ui.checkbox(&mut self.normalize_shares, "");
+                      ui.add_space(10.0);
+                      ui.end_row();
+                  });
+
+                  if ui.button("Check").clicked() {
+                      self.dataset_warnings = match load_problem(&self.problem_file_path, &self.divide_file_path, &self.properties_file_path, self.heir_count, self.normalize_shares) {
+                          Ok((mut problem, mut warnings)) => {
+                              set_constraint_settings(&mut self.model.problem.constraints, &mut self.constraints_file_path, &self.problem_file_path, &mut problem, &mut warnings);
+
+                              match warnings.is_empty() {
+                                  true => vec!["No warnings".to_string()],
+                                  false => warnings,
+                              }
+                          }
+                          Err(err) => vec![err],
+                      };
+                  }
+
+                  for warning in &self.dataset_warnings {
+                      ui.colored_label(egui::Color32::YELLOW, warning);
+                  }
               });
 
               ui.collapsing("Parameters", |ui| {
                   egui::Grid::new("parameters_grid").show(ui, |ui| {
                       ui.label("Generation");
-                      ui.add(egui::Slider::new(&mut self.generation, 0..=100000));
+                      ui.add_enabled(!locked, egui::Slider::new(&mut self.generation, 0..=100000));
                       ui.add_space(10.0);
                       ui.end_row();
 
                       ui.label("Gene Len");
-                      ui.add(egui::Slider::new(&mut self.model.gene_len, 0..=100000));
+                      ui.add_enabled(!locked, egui::Slider::new(&mut self.model.gene_len, 0..=100000));
                       ui.add_space(10.0);
                       ui.end_row();
 
@@ -129,6 +249,56 @@ impl eframe::App for GeneApp {
                       ui.add_space(10.0);
                       ui.end_row();
 
+                      ui.label("Mutation");
+                      egui::ComboBox::from_id_source("mutation_kind")
+                          .selected_text(self.model.mutation_kind.to_string())
+                          .show_ui(ui, |ui| {
+                              for kind in MutationKind::ALL {
+                                  ui.selectable_value(&mut self.model.mutation_kind, kind, kind.to_string());
+                              }
+                          });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      if self.model.mutation_kind == MutationKind::Bernoulli {
+                          ui.label("Mutation Rate");
+                          ui.add(egui::Slider::new(&mut self.model.mutation_rate, 0.0..=1.0));
+                          ui.add_space(10.0);
+                          ui.end_row();
+                      }
+
+                      ui.label("Mutation Schedule");
+                      egui::ComboBox::from_id_source("mutation_schedule")
+                          .selected_text(self.model.mutation_schedule.to_string())
+                          .show_ui(ui, |ui| {
+                              for schedule in MutationSchedule::ALL {
+                                  ui.selectable_value(&mut self.model.mutation_schedule, schedule, schedule.to_string());
+                              }
+                          });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      match self.model.mutation_schedule {
+                          MutationSchedule::Decay => {
+                              ui.label("Mutation Decay");
+                              ui.add(egui::Slider::new(&mut self.model.mutation_decay, 0.9..=1.0));
+                              ui.add_space(10.0);
+                              ui.end_row();
+                          }
+                          MutationSchedule::Adaptive => {
+                              ui.label("Stagnation Generations");
+                              ui.add(egui::Slider::new(&mut self.model.stagnation_generations, 1..=10000));
+                              ui.add_space(10.0);
+                              ui.end_row();
+
+                              ui.label("Stagnation Boost");
+                              ui.add(egui::Slider::new(&mut self.model.stagnation_boost, 1.0..=10.0));
+                              ui.add_space(10.0);
+                              ui.end_row();
+                          }
+                          _ => {}
+                      }
+
                       ui.label("Elite Conservation Probability");
                       ui.add(egui::Slider::new(&mut self.model.elite_conservation_probability, 0.0..=1.0));
                       ui.add_space(10.0);
@@ -138,33 +308,378 @@ impl eframe::App for GeneApp {
                       ui.add(egui::Slider::new(&mut self.model.total_conservation_probability, 0.0..=1.0));
                       ui.add_space(10.0);
                       ui.end_row();
+
+                      ui.label("Selection");
+                      egui::ComboBox::from_id_source("selection_kind")
+                          .selected_text(self.model.selection_kind.to_string())
+                          .show_ui(ui, |ui| {
+                              for kind in SelectionKind::ALL {
+                                  ui.selectable_value(&mut self.model.selection_kind, kind, kind.to_string());
+                              }
+                          });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      match self.model.selection_kind {
+                          SelectionKind::Tournament => {
+                              ui.label("Tournament Size");
+                              ui.add(egui::Slider::new(&mut self.model.tournament_size, 1..=100));
+                              ui.add_space(10.0);
+                              ui.end_row();
+                          }
+                          SelectionKind::Rank => {
+                              ui.label("Rank Pressure");
+                              ui.add(egui::Slider::new(&mut self.model.rank_pressure, 1.0..=2.0));
+                              ui.add_space(10.0);
+                              ui.end_row();
+                          }
+                          _ => {}
+                      }
+
+                      ui.label("Crossover");
+                      ui.add_enabled_ui(!locked, |ui| {
+                          egui::ComboBox::from_id_source("crossover_kind")
+                              .selected_text(self.model.crossover_kind.to_string())
+                              .show_ui(ui, |ui| {
+                                  for kind in CrossoverKind::ALL {
+                                      ui.selectable_value(&mut self.model.crossover_kind, kind, kind.to_string());
+                                  }
+                              });
+                      });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      match self.model.crossover_kind {
+                          CrossoverKind::KPoint => {
+                              ui.label("Crossover Points");
+                              ui.add_enabled(!locked, egui::Slider::new(&mut self.model.crossover_points, 1..=100));
+                              ui.add_space(10.0);
+                              ui.end_row();
+                          }
+                          CrossoverKind::Uniform => {
+                              ui.label("Uniform Bias");
+                              ui.add_enabled(!locked, egui::Slider::new(&mut self.model.uniform_bias, 0.0..=1.0));
+                              ui.add_space(10.0);
+                              ui.end_row();
+                          }
+                          _ => {}
+                      }
+
+                      ui.label("Crossover Probability");
+                      ui.add_enabled(!locked, egui::Slider::new(&mut self.model.crossover_probability, 0.0..=1.0));
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Diversity Metric");
+                      ui.add_enabled_ui(!locked, |ui| {
+                          egui::ComboBox::from_id_source("diversity_metric")
+                              .selected_text(self.model.diversity_metric.to_string())
+                              .show_ui(ui, |ui| {
+                                  for metric in DiversityMetric::ALL {
+                                      ui.selectable_value(&mut self.model.diversity_metric, metric, metric.to_string());
+                                  }
+                              });
+                      });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      if self.model.diversity_metric == DiversityMetric::Genotype {
+                          ui.label("Diversity Samples");
+                          ui.add_enabled(!locked, egui::Slider::new(&mut self.model.diversity_samples, 1..=100000));
+                          ui.add_space(10.0);
+                          ui.end_row();
+                      }
+
+                      ui.label("Fitness Metric");
+                      ui.add_enabled_ui(!locked, |ui| {
+                          egui::ComboBox::from_id_source("fitness_metric")
+                              .selected_text(self.model.problem.metric.to_string())
+                              .show_ui(ui, |ui| {
+                                  for metric in FitnessMetric::ALL {
+                                      ui.selectable_value(&mut self.model.problem.metric, metric, metric.to_string())
+                                          .on_hover_text(metric.explanation());
+                                  }
+                              })
+                              .response
+                              .on_hover_text(self.model.problem.metric.explanation());
+                      });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      // Pinned items, forbidden heirs and item limits come
+                      // from the problem file, and so do these settings when
+                      // it is loaded
+                      ui.label("Constraints");
+                      ui.horizontal(|ui| {
+                          ui.set_enabled(!locked);
+                          egui::ComboBox::from_id_source("constraint_handling")
+                              .selected_text(self.model.problem.constraints.handling.to_string())
+                              .show_ui(ui, |ui| {
+                                  for handling in ConstraintHandling::ALL {
+                                      ui.selectable_value(&mut self.model.problem.constraints.handling, handling, handling.to_string());
+                                  }
+                              });
+                          ui.label("penalty");
+                          ui.add(egui::DragValue::new(&mut self.model.problem.constraints.penalty).speed(0.01).clamp_range(0.0..=1000.0));
+                      });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Objectives");
+                      ui.add_enabled_ui(!locked, |ui| {
+                          egui::ComboBox::from_id_source("objective_mode")
+                              .selected_text(self.model.objective_mode.to_string())
+                              .show_ui(ui, |ui| {
+                                  for mode in ObjectiveMode::ALL {
+                                      ui.selectable_value(&mut self.model.objective_mode, mode, mode.to_string());
+                                  }
+                              });
+                      });
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Islands");
+                      ui.add_enabled(!locked, egui::Slider::new(&mut self.island_parameters.island_count, 1..=32));
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      if self.island_parameters.island_count > 1 {
+                          ui.label("Migration Interval");
+                          ui.add_enabled(!locked, egui::Slider::new(&mut self.island_parameters.migration_interval, 1..=1000));
+                          ui.add_space(10.0);
+                          ui.end_row();
+
+                          ui.label("Migrant Count");
+                          ui.add_enabled(!locked, egui::Slider::new(&mut self.island_parameters.migrant_count, 1..=100));
+                          ui.add_space(10.0);
+                          ui.end_row();
+
+                          ui.label("Topology");
+                          ui.add_enabled_ui(!locked, |ui| {
+                              egui::ComboBox::from_id_source("topology")
+                                  .selected_text(self.island_parameters.topology.to_string())
+                                  .show_ui(ui, |ui| {
+                                      for topology in Topology::ALL {
+                                          ui.selectable_value(&mut self.island_parameters.topology, topology, topology.to_string());
+                                      }
+                                  });
+                          });
+                          ui.add_space(10.0);
+                          ui.end_row();
+
+                          ui.label("Replacement");
+                          ui.add_enabled_ui(!locked, |ui| {
+                              egui::ComboBox::from_id_source("replacement")
+                                  .selected_text(self.island_parameters.replacement.to_string())
+                                  .show_ui(ui, |ui| {
+                                      for replacement in Replacement::ALL {
+                                          ui.selectable_value(&mut self.island_parameters.replacement, replacement, replacement.to_string());
+                                      }
+                                  });
+                          });
+                          ui.add_space(10.0);
+                          ui.end_row();
+                      }
+
+                      ui.label("Seed");
+                      ui.horizontal(|ui| {
+                          ui.set_enabled(!locked);
+                          ui.add(egui::DragValue::new(&mut self.model.seed));
+
+                          if ui.button("Random").clicked() {
+                              self.model.seed = rand::random::<u32>() as u64;
+                          }
+                      });
+                      ui.add_space(10.0);
+                      ui.end_row();
+                  });
+              });
+
+              ui.collapsing("Checkpoint", |ui| {
+                  ui.set_enabled(!locked);
+
+                  ui.label("Checkpoint File Path");
+                  ui.text_edit_singleline(&mut self.checkpoint_file_path);
+                  ui.add_space(10.0);
+
+                  egui::Grid::new("checkpoint_grid").show(ui, |ui| {
+                      ui.label("Save Checkpoints");
+                      ui.checkbox(&mut self.save_checkpoints, "");
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Checkpoint Interval (0: on stop only)");
+                      ui.add(egui::Slider::new(&mut self.model.checkpoint_interval, 0..=10000));
+                      ui.add_space(10.0);
+                      ui.end_row();
+                  });
+              });
+
+              ui.collapsing("Stopping Criteria", |ui| {
+                  ui.set_enabled(!locked);
+
+                  egui::Grid::new("termination_grid").show(ui, |ui| {
+                      ui.label("Target Fitness");
+                      optional_value(ui, &mut self.model.termination.target_fitness, 0.01);
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Stagnation Limit");
+                      optional_value(ui, &mut self.model.termination.stagnation_limit, 500);
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Time Limit (seconds)");
+                      optional_value(ui, &mut self.model.termination.time_limit_seconds, 60.0);
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Max Evaluations");
+                      optional_value(ui, &mut self.model.termination.max_evaluations, 1000000);
+                      ui.add_space(10.0);
+                      ui.end_row();
+
+                      ui.label("Min Diversity");
+                      optional_value(ui, &mut self.model.termination.min_diversity, 0.01);
+                      ui.add_space(10.0);
+                      ui.end_row();
                   });
               });
 
               if self.model.tracker.is_running {
-                if ui.button("Fit stop").clicked() {
-                    self.sender.send(true).unwrap();
+                // The worker may already be done, so failed sends are ignored
+                ui.horizontal(|ui| {
+                    if ui.button("Fit stop").clicked() {
+                        self.sender.send(Command::Stop).ok();
+                    }
+
+                    if self.model.tracker.is_paused {
+                        if ui.button("Resume").clicked() {
+                            self.sender.send(Command::Resume).ok();
+                        }
+                    } else if ui.button("Pause").clicked() {
+                        self.sender.send(Command::Pause).ok();
+                    }
+
+                    if ui.button("Step").clicked() {
+                        self.sender.send(Command::Step(self.step_generations)).ok();
+                    }
+                    ui.add(egui::DragValue::new(&mut self.step_generations).clamp_range(1..=10000).suffix(" generations"));
+                });
+
+                // Mutation and selection parameters take effect from the next generation
+                if ui.button("Apply parameters").clicked() {
+                    self.start_error = match self.model.validate() {
+                        Ok(()) => {
+                            self.sender.send(Command::SetParameters(self.model.live_parameters())).ok();
+                            None
+                        }
+                        Err(err) => Some(format!("Parameter error: {}", err)),
+                    };
+                }
+
+                if let Some(err) = &self.start_error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+
+                for change in &self.model.tracker.parameter_changes {
+                    ui.label(format!("Parameters changed at generation {}", change.generation));
                 }
               } else {
                 if ui.button("Fit start").clicked() {
                     // Load datasets
-                    let (divide, properties) = load_dataset(&self.divide_file_path, &self.properties_file_path);
+                    self.start_error = match load_problem(&self.problem_file_path, &self.divide_file_path, &self.properties_file_path, self.heir_count, self.normalize_shares) {
+                        Ok((mut problem, mut warnings)) => {
+                            // The metric is chosen in the window, not in the dataset
+                            problem.metric = self.model.problem.metric;
+                            set_constraint_settings(&mut self.model.problem.constraints, &mut self.constraints_file_path, &self.problem_file_path, &mut problem, &mut warnings);
+                            self.model.problem = problem;
+                            self.dataset_warnings = warnings;
+
+                            // Island checks include the model ones
+                            let result = match self.save_checkpoints && self.island_parameters.island_count > 1 {
+                                true => Err("checkpoints are not supported with islands".to_string()),
+                                false => IslandModel::new(self.model.clone(), self.island_parameters.clone()).validate(),
+                            };
 
-                    self.model.divide = divide;
-                    self.model.properties = properties;
+                            result.err().map(|err| format!("Parameter error: {}", err))
+                        }
+                        Err(err) => Some(err),
+                    };
 
-                    // Open windows
-                    self.plot_window = true;
-                    self.fit_results_window = true;
+                    if self.start_error.is_none() {
+                        // Open windows
+                        self.plot_window = true;
+                        self.fit_results_window = true;
+                        self.pareto_window = self.model.objective_mode == ObjectiveMode::Nsga2;
 
-                    // Set model
-                    let mut model = self.model.clone();
-                    let generation = self.generation;
+                        // Set model
+                        self.model.checkpoint_path = checkpoint_path(self.save_checkpoints, &self.checkpoint_file_path);
+                        let mut model = self.model.clone();
+                        let generation = self.generation;
 
-                    // Fit start
-                    let (handler, tx, rx) = model.fit_back(generation);
-                    self.sender = tx;
-                    self.receiver = rx;
+                        // Logs of the run
+                        let (log_tx, log_rx) = mpsc::channel();
+                        model.observers.add(LogObserver::new(log_tx));
+                        self.log_receiver = log_rx;
+
+                        for warning in &self.dataset_warnings {
+                            self.logs.push(LogEntry::new(LogLevel::Warn, 0, warning.clone()));
+                        }
+
+                        // Fit start (one population, or islands)
+                        let (handler, tx, rx) = if self.island_parameters.island_count > 1 {
+                            IslandModel::new(model, self.island_parameters.clone()).fit_back(generation)
+                        } else {
+                            model.fit_back(generation)
+                        };
+                        self.sender = tx;
+                        self.receiver = rx;
+                        self.handler = Some(handler);
+                    }
+                }
+
+                if ui.button("Load checkpoint").clicked() {
+                    self.start_error = match Checkpoint::<PropertyDistribution>::load(Path::new(&self.checkpoint_file_path)) {
+                        Ok(checkpoint) => {
+                            let generations = checkpoint.generations;
+                            let model = GAModel::from_checkpoint(checkpoint);
+
+                            match model.validate() {
+                                Ok(()) => {
+                                    self.generation = generations;
+                                    self.model = model;
+                                    self.plot_outdated = true;
+                                    self.model.checkpoint_path = checkpoint_path(self.save_checkpoints, &self.checkpoint_file_path);
+
+                                    // Continue from the saved generation
+                                    self.plot_window = true;
+                                    self.fit_results_window = true;
+
+                                    let (log_tx, log_rx) = mpsc::channel();
+                                    self.model.observers.add(LogObserver::new(log_tx));
+                                    self.log_receiver = log_rx;
+
+                                    let (handler, tx, rx) = self.model.resume_back(self.generation);
+                                    self.sender = tx;
+                                    self.receiver = rx;
+                                    self.handler = Some(handler);
+                                    None
+                                }
+                                Err(err) => Some(format!("Parameter error: {}", err)),
+                            }
+                        }
+                        Err(err) => Some(format!("Checkpoint error: {}", err)),
+                    };
+                }
+
+                if let Some(err) = &self.start_error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+
+                // Dataset warnings do not stop the fitting
+                for warning in &self.dataset_warnings {
+                    ui.colored_label(egui::Color32::YELLOW, warning);
                 }
               }
         });
@@ -173,8 +688,52 @@ impl eframe::App for GeneApp {
           .default_size(egui::Vec2::new(1000.0, 500.0))
           .open(&mut self.logs_window)
           .show(ctx, |ui| {
-              egui::ScrollArea::vertical().show(ui, |ui| {
-                  ui.label("hello");
+              ui.horizontal(|ui| {
+                  ui.label("Level");
+                  egui::ComboBox::from_id_source("log_level")
+                      .selected_text(self.log_level.to_string())
+                      .show_ui(ui, |ui| {
+                          for level in LogLevel::ALL {
+                              ui.selectable_value(&mut self.log_level, level, level.to_string());
+                          }
+                      });
+
+                  ui.label("Search");
+                  ui.text_edit_singleline(&mut self.log_search);
+
+                  ui.checkbox(&mut self.log_auto_scroll, "Auto Scroll");
+
+                  if ui.button("Clear").clicked() {
+                      self.logs.clear();
+                  }
+              });
+
+              // Only the shown entries are saved
+              ui.horizontal(|ui| {
+                  ui.text_edit_singleline(&mut self.log_file_path);
+
+                  if ui.button("Save").clicked() {
+                      let entries = self.logs.iter().filter(|entry| log_matches(entry, self.log_level, &self.log_search));
+
+                      self.log_status = match save_logs(entries, Path::new(&self.log_file_path)) {
+                          Ok(()) => format!("Saved to {}", self.log_file_path),
+                          Err(err) => format!("Save failed: {}", err),
+                      };
+                  }
+              });
+              ui.label(&self.log_status);
+              ui.add_space(10.0);
+
+              egui::ScrollArea::vertical().stick_to_bottom(self.log_auto_scroll).show(ui, |ui| {
+                  for entry in self.logs.iter().filter(|entry| log_matches(entry, self.log_level, &self.log_search)) {
+                      let color = match entry.level {
+                          LogLevel::Info => ui.visuals().text_color(),
+                          LogLevel::Warn => egui::Color32::YELLOW,
+                          LogLevel::Error => egui::Color32::RED,
+                      };
+
+                      ui.colored_label(color, entry.to_string());
+                  }
               });
           });
 
@@ -182,27 +741,64 @@ impl eframe::App for GeneApp {
           .default_size(egui::Vec2::new(1000.0, 300.0))
           .open(&mut self.plot_window)
           .show(ctx, |ui| {
-              let best_fitness_changes: PlotPoints = (0..self.model.tracker.total_generation).map(|i| {
-                  let x = i as f64;
-                  [x, self.model.tracker.best_fitness_changes[i as usize]]
-              }).collect();
+              if self.plot_outdated {
+                  let tracker = &self.model.tracker;
 
-              let average_fitness_changes: PlotPoints = (0..self.model.tracker.total_generation).map(|i| {
-                  let x = i as f64;
-                  [x, self.model.tracker.average_fitness_changes[i as usize]]
-              }).collect();
+                  self.plot_lines = vec![
+                      ("best".to_string(), downsample(&tracker.best_fitness_changes, PLOT_POINTS)),
+                      ("average".to_string(), downsample(&tracker.average_fitness_changes, PLOT_POINTS)),
+                      ("diff".to_string(), downsample(&tracker.average_diff_changes, PLOT_POINTS)),
+                  ];
 
-              let average_diff_changes: PlotPoints = (0..self.model.tracker.total_generation).map(|i| {
-                  let x = i as f64;
-                  [x, self.model.tracker.average_diff_changes[i as usize]]
-              }).collect();
+                  for (island, changes) in tracker.island_best_fitness_changes.iter().enumerate() {
+                      self.plot_lines.push((format!("island {}", island), downsample(changes, PLOT_POINTS)));
+                  }
+
+                  self.plot_outdated = false;
+              }
 
               Plot::new("fitness changes")
                   .view_aspect(2.0)
+                  .legend(Legend::default())
                   .show(ui, |plot_ui| {
-                      plot_ui.line(Line::new(best_fitness_changes));
-                      plot_ui.line(Line::new(average_fitness_changes));
-                      plot_ui.line(Line::new(average_diff_changes));
+                      for (name, points) in &self.plot_lines {
+                          plot_ui.line(Line::new(PlotPoints::new(points.clone())).name(name));
+                      }
+                  });
+          });
+
+        egui::Window::new("Pareto Front")
+          .default_size(egui::Vec2::new(600.0, 400.0))
+          .open(&mut self.pareto_window)
+          .show(ctx, |ui| {
+              let tracker = &self.model.tracker;
+
+              if tracker.pareto_front.is_empty() {
+                  ui.label("No Pareto front (objectives: nsga2)");
+                  return;
+              }
+
+              ui.horizontal(|ui| {
+                  for (id, axis) in [("pareto_x", &mut self.pareto_x), ("pareto_y", &mut self.pareto_y)] {
+                      egui::ComboBox::from_id_source(id)
+                          .selected_text(tracker.objective_names.get(*axis).cloned().unwrap_or_default())
+                          .show_ui(ui, |ui| {
+                              for (objective, name) in tracker.objective_names.iter().enumerate() {
+                                  ui.selectable_value(axis, objective, name);
+                              }
+                          });
+                  }
+              });
+              ui.label(format!("{} genes", tracker.pareto_front.len()));
+
+              let points: Vec<[f64; 2]> = tracker.pareto_front.iter()
+                  .filter_map(|gene| Some([*gene.objectives.get(self.pareto_x)?, *gene.objectives.get(self.pareto_y)?]))
+                  .collect();
+
+              Plot::new("pareto front")
+                  .view_aspect(1.5)
+                  .show(ui, |plot_ui| {
+                      plot_ui.points(Points::new(points).radius(3.0).name("front"));
                   });
           });
 
@@ -215,21 +811,149 @@ impl eframe::App for GeneApp {
               ui.add(egui::widgets::ProgressBar::new(self.model.tracker.total_generation as f32 / self.generation as f32));
               ui.add_space(10.0);
 
-              ui.label("Gene Data: ");
-              ui.label(format!("{:?}", self.model.tracker.best_gene.data));
+              let breakdown = self.model.problem.breakdown(&self.model.tracker.best_gene.data);
+
+              ui.collapsing("Allocation", |ui| {
+                  egui::Grid::new("allocation_grid").striped(true).show(ui, |ui| {
+                      ui.strong("Heir");
+                      ui.strong("Target Share");
+                      ui.strong("Achieved Share");
+                      ui.strong("Value");
+                      ui.strong("Deviation");
+                      ui.strong("Items");
+                      ui.end_row();
+
+                      for allocation in &breakdown {
+                          let is_selected = self.selected_heir == Some(allocation.heir);
+
+                          if ui.selectable_label(is_selected, self.model.problem.heir_name(allocation.heir)).clicked() {
+                              self.selected_heir = match is_selected {
+                                  true => None,
+                                  false => Some(allocation.heir),
+                              };
+                          }
+                          ui.label(format!("{:.4}", allocation.target_share));
+                          ui.label(format!("{:.4}", allocation.share));
+                          ui.label(allocation.value.to_string());
+                          ui.label(format!("{:+.4}", allocation.deviation));
+                          ui.label(allocation.items.len().to_string());
+                          ui.end_row();
+                      }
+                  });
+                  ui.add_space(10.0);
+
+                  // Target and achieved bars side by side
+                  let target_bars: Vec<Bar> = breakdown.iter()
+                      .map(|allocation| Bar::new(allocation.heir as f64 - 0.2, allocation.target_share).width(0.4))
+                      .collect();
+
+                  let achieved_bars: Vec<Bar> = breakdown.iter()
+                      .map(|allocation| Bar::new(allocation.heir as f64 + 0.2, allocation.share).width(0.4))
+                      .collect();
+
+                  Plot::new("allocation")
+                      .view_aspect(3.0)
+                      .legend(Legend::default())
+                      .show(ui, |plot_ui| {
+                          plot_ui.bar_chart(BarChart::new(target_bars).name("target"));
+                          plot_ui.bar_chart(BarChart::new(achieved_bars).name("achieved"));
+                      });
+
+                  if let Some(allocation) = self.selected_heir.and_then(|heir| breakdown.get(heir)) {
+                      ui.add_space(10.0);
+                      ui.label(format!("Properties of {}", self.model.problem.heir_name(allocation.heir)));
+
+                      egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                          egui::Grid::new("heir_items_grid").striped(true).show(ui, |ui| {
+                              for item in &allocation.items {
+                                  ui.label(item.to_string());
+                                  ui.label(self.model.problem.item_name(*item));
+                                  ui.label(self.model.problem.properties[*item].to_string());
+                                  ui.end_row();
+                              }
+                          });
+                      });
+                  }
+              });
+              ui.add_space(10.0);
+
+              ui.collapsing("Gene Data", |ui| {
+                  ui.label(format!("{:?}", self.model.tracker.best_gene.data));
+              });
               ui.add_space(10.0);
 
+              ui.label(format!("Generations: {}, Evaluations: {}", self.model.tracker.total_generation, self.model.tracker.evaluations));
+
+              if self.model.tracker.is_paused {
+                  ui.label("Paused");
+              }
+
+              if let Some(stop_reason) = self.model.tracker.stop_reason {
+                  ui.label(format!("Stop Reason: {}", stop_reason));
+              }
+              ui.add_space(10.0);
+
+              if !self.model.problem.item_names.is_empty() {
+                  ui.collapsing("Assignment", |ui| {
+                      egui::Grid::new("assignment_grid").show(ui, |ui| {
+                          for (item, heir) in self.model.problem.assignment(&self.model.tracker.best_gene.data) {
+                              ui.label(item);
+                              ui.label("→");
+                              ui.label(heir);
+                              ui.end_row();
+                          }
+                      });
+                  });
+                  ui.add_space(10.0);
+              }
+
               ui.label("Fitness: ");
               ui.label(format!("{}", self.model.tracker.best_gene.fitness));
               ui.add_space(10.0);
 
-              ui.label("Real Fitness: ");
-              let properties_sum: i32 = self.model.properties.iter().sum();
-              ui.label(format!("{}", self.model.tracker.best_gene.fitness * properties_sum as f64));
+              let metric = self.model.problem.metric;
+              ui.label(format!("{} ({}): ", metric.real_name(), metric));
+              ui.label(format!("{}", self.model.problem.real_fitness(&self.model.tracker.best_gene.data)));
+              ui.small(metric.explanation());
+              ui.add_space(10.0);
+
+              if !self.model.problem.constraints.is_empty() {
+                  let violations = self.model.problem.violations(&self.model.tracker.best_gene.data);
+
+                  ui.label(format!("Constraints ({}): {} violated", self.model.problem.constraints.handling, violations.len()));
+                  for violation in violations {
+                      ui.colored_label(egui::Color32::RED, violation);
+                  }
+                  ui.add_space(10.0);
+              }
+
+              ui.label("Export (.json or .csv): ");
+              ui.horizontal(|ui| {
+                  ui.text_edit_singleline(&mut self.export_file_path);
+
+                  if ui.button("Export").clicked() {
+                      self.export_status = match export(&self.model.tracker, Path::new(&self.export_file_path)) {
+                          Ok(()) => format!("Exported to {}", self.export_file_path),
+                          Err(err) => format!("Export failed: {}", err),
+                      };
+                  }
+              });
+              ui.label(&self.export_status);
           });
 
         ctx.request_repaint();
     }
+
+    // A running fit is stopped first so that it saves its last checkpoint
+    fn on_close_event(&mut self) -> bool {
+        if let Some(handler) = self.handler.take() {
+            if self.sender.send(Command::Stop).is_ok() {
+                handler.join().ok();
+            }
+        }
+
+        true
+    }
 }
 
 impl GeneApp {
@@ -243,3 +967,112 @@ impl GeneApp {
         );
     }
 }
+
+// Loads and validates the dataset. Warnings are returned next to the problem
+// since they do not stop the fitting.
+fn load_problem(problem_file_path: &str, divide_file_path: &str, properties_file_path: &str, heir_count: usize, normalize: bool) -> Result<(PropertyDistribution, Vec<String>), String> {
+    let mut problem = if problem_file_path.is_empty() {
+        let (divide, properties) = load_dataset(divide_file_path, properties_file_path)
+            .map_err(|err| format!("Dataset error: {}", err))?;
+
+        PropertyDistribution::new(divide, properties)
+    } else {
+        load_problem_file(problem_file_path)
+            .map_err(|err| format!("Dataset error: {}", err))?
+            .problem()
+    };
+
+    if normalize {
+        normalize_shares(&mut problem.divide);
+    }
+
+    let heir_count = match heir_count {
+        0 if problem_file_path.is_empty() => expected_share_count(Path::new(divide_file_path)),
+        0 => None,
+        heir_count => Some(heir_count),
+    };
+
+    let warnings = validate_dataset(&problem.divide, &problem.properties, heir_count).warnings();
+
+    Ok((problem, warnings))
+}
+
+// The Constraints row (`constraints`) takes the settings of a problem file
+// when it is first loaded. Settings changed in the row afterwards are kept,
+// with a warning while they differ from the file.
+fn set_constraint_settings(constraints: &mut Constraints, constraints_file_path: &mut String, problem_file_path: &str, problem: &mut PropertyDistribution, warnings: &mut Vec<String>) {
+    if constraints_file_path != problem_file_path {
+        *constraints_file_path = problem_file_path.to_string();
+        constraints.handling = problem.constraints.handling;
+        constraints.penalty = problem.constraints.penalty;
+        return;
+    }
+
+    let is_changed = constraints.handling != problem.constraints.handling || constraints.penalty != problem.constraints.penalty;
+
+    if is_changed && !problem.constraints.is_empty() {
+        warnings.push(format!(
+            "Constraint handling {} with penalty {} instead of {} with penalty {} of the problem file",
+            constraints.handling, constraints.penalty, problem.constraints.handling, problem.constraints.penalty,
+        ));
+    }
+
+    problem.constraints.handling = constraints.handling;
+    problem.constraints.penalty = constraints.penalty;
+}
+
+// Entries at or above `level` that contain `search` (case insensitive)
+fn log_matches(entry: &LogEntry, level: LogLevel, search: &str) -> bool {
+    entry.level >= level && entry.message.to_lowercase().contains(&search.to_lowercase())
+}
+
+// Points per plot line, whatever the generation count
+const PLOT_POINTS: usize = 2000;
+
+// At most `max_points` points: the lowest and the highest value of every
+// bucket, so that spikes stay visible
+fn downsample(values: &[f64], max_points: usize) -> Vec<[f64; 2]> {
+    if values.len() <= max_points {
+        return values.iter().enumerate().map(|(x, y)| [x as f64, *y]).collect();
+    }
+
+    let bucket_len = values.len().div_ceil(max_points / 2);
+    let mut points = Vec::with_capacity(max_points);
+
+    for (n, bucket) in values.chunks(bucket_len).enumerate() {
+        let start = n * bucket_len;
+        let min = bucket.iter().enumerate().min_by(|a, b| a.1.total_cmp(b.1)).unwrap();
+        let max = bucket.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap();
+
+        let (first, second) = if min.0 <= max.0 { (min, max) } else { (max, min) };
+
+        points.push([(start + first.0) as f64, *first.1]);
+        if first.0 != second.0 {
+            points.push([(start + second.0) as f64, *second.1]);
+        }
+    }
+
+    points
+}
+
+fn checkpoint_path(save_checkpoints: bool, checkpoint_file_path: &str) -> Option<PathBuf> {
+    match save_checkpoints {
+        true => Some(PathBuf::from(checkpoint_file_path)),
+        false => None,
+    }
+}
+
+// Checkbox that turns an optional value on, followed by its value
+fn optional_value<T: egui::emath::Numeric>(ui: &mut egui::Ui, value: &mut Option<T>, default: T) {
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+
+        if ui.checkbox(&mut enabled, "").changed() {
+            *value = enabled.then_some(default);
+        }
+
+        if let Some(value) = value {
+            ui.add(egui::DragValue::new(value));
+        }
+    });
+}
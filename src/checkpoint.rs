@@ -0,0 +1,100 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+use super::model::{GAParameters, GAModelTracker, Gene};
+
+// Everything needed to continue a run exactly where it stopped
+//  - generations: the generation count the run was started with
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Checkpoint<P> {
+    pub generations: usize,
+    pub parameters: GAParameters,
+    pub problem: P,
+    pub genes: Vec<Gene>,
+    // Only used by `ObjectiveMode::Nsga2`
+    #[serde(default)]
+    pub survivors: Vec<Gene>,
+    pub rng: ChaCha8Rng,
+    pub tracker: GAModelTracker,
+}
+
+impl<P: Serialize + DeserializeOwned> Checkpoint<P> {
+    // Written to a temporary file first so that a crash never leaves a
+    // half written checkpoint behind
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let temp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        drop(writer);
+
+        std::fs::rename(temp_path, path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::model::GAModel;
+    use crate::nsga::ObjectiveMode;
+    use crate::problem::PropertyDistribution;
+
+    fn model(objective_mode: ObjectiveMode) -> GAModel<PropertyDistribution> {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let properties = (0..50).map(|_n| rng.gen_range(1..1000)).collect();
+
+        let mut model = GAModel::new(PropertyDistribution::new(vec![0.2; 5], properties));
+        model.gene_len = 40;
+        model.objective_mode = objective_mode;
+        model.seed = 17;
+
+        model
+    }
+
+    #[test]
+    fn resumed_run_matches_uninterrupted_run() {
+        for objective_mode in ObjectiveMode::ALL {
+            let path = std::env::temp_dir().join(format!("rust_genetic_algorithm-{}-{}.json", std::process::id(), objective_mode));
+
+            let mut uninterrupted = model(objective_mode);
+            uninterrupted.run(20);
+
+            let mut interrupted = model(objective_mode);
+            interrupted.checkpoint_path = Some(path.clone());
+            interrupted.run(10);
+
+            let checkpoint = Checkpoint::<PropertyDistribution>::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(checkpoint.tracker.total_generation, 10);
+
+            let mut resumed = GAModel::from_checkpoint(checkpoint);
+            resumed.resume(20);
+
+            let (expected, actual) = (&uninterrupted.tracker, &resumed.tracker);
+            assert_eq!(actual.total_generation, 20);
+            assert_eq!(actual.best_gene.data, expected.best_gene.data);
+            assert_eq!(actual.best_generation, expected.best_generation);
+            assert_eq!(actual.best_fitness_changes, expected.best_fitness_changes);
+            assert_eq!(actual.average_fitness_changes, expected.average_fitness_changes);
+            assert_eq!(actual.average_diff_changes, expected.average_diff_changes);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use rand::prelude::{Rng, SliceRandom};
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+
+use super::model::Gene;
+
+// How the operators deal with the constraints
+//  - Repair: every gene made by init, crossover or mutation is fixed
+//  - Penalty: genes are left as they are and pay a penalty per violation
+// A violation that repair cannot fix is penalized as well.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConstraintHandling {
+    #[default]
+    Repair,
+    Penalty,
+}
+
+named_enum!(ConstraintHandling, "constraint handling", {
+    Repair => "repair",
+    Penalty => "penalty",
+});
+
+// An item and a heir, by index
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub item: usize,
+    pub heir: usize,
+}
+
+// Item count limits of a heir
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemLimit {
+    pub heir: usize,
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+// Hard rules of an allocation
+//  - pinned: the item must go to the heir
+//  - forbidden: the item must not go to the heir
+//  - item_limits: at least `min` and at most `max` items for the heir
+//  - penalty: added to the fitness per violated rule (per missing or extra
+//    item for the limits), as a fraction of the worst fitness of the metric
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Constraints {
+    pub pinned: Vec<Assignment>,
+    pub forbidden: Vec<Assignment>,
+    pub item_limits: Vec<ItemLimit>,
+    pub handling: ConstraintHandling,
+    pub penalty: f64,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Self {
+            pinned: Vec::new(),
+            forbidden: Vec::new(),
+            item_limits: Vec::new(),
+            handling: ConstraintHandling::Repair,
+            penalty: 1.0,
+        }
+    }
+}
+
+// A rule broken by a gene
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    Pinned { item: usize, heir: usize, actual: usize },
+    Forbidden { item: usize, heir: usize },
+    TooFewItems { heir: usize, count: usize, min: usize },
+    TooManyItems { heir: usize, count: usize, max: usize },
+}
+
+impl Violation {
+    // Units of the penalty
+    pub fn amount(&self) -> usize {
+        match self {
+            Violation::Pinned { .. } | Violation::Forbidden { .. } => 1,
+            Violation::TooFewItems { count, min, .. } => min - count,
+            Violation::TooManyItems { count, max, .. } => count - max,
+        }
+    }
+}
+
+impl Constraints {
+    pub fn is_empty(&self) -> bool {
+        self.pinned.is_empty() && self.forbidden.is_empty() && self.item_limits.is_empty()
+    }
+
+    pub fn pinned_heir(&self, item: usize) -> Option<usize> {
+        self.pinned.iter().find(|pin| pin.item == item).map(|pin| pin.heir)
+    }
+
+    pub fn is_forbidden(&self, item: usize, heir: usize) -> bool {
+        self.forbidden.iter().any(|forbidden| forbidden.item == item && forbidden.heir == heir)
+    }
+
+    // Whether the item may go to the heir, whatever the item counts
+    pub fn is_allowed(&self, item: usize, heir: usize) -> bool {
+        self.pinned_heir(item).is_none_or(|pinned| pinned == heir) && !self.is_forbidden(item, heir)
+    }
+
+    pub fn min_items(&self, heir: usize) -> usize {
+        self.item_limits.iter().filter(|limit| limit.heir == heir).filter_map(|limit| limit.min).max().unwrap_or(0)
+    }
+
+    pub fn max_items(&self, heir: usize) -> usize {
+        self.item_limits.iter().filter(|limit| limit.heir == heir).filter_map(|limit| limit.max).min().unwrap_or(usize::MAX)
+    }
+
+    // Rules that no allocation of `item_count` items to `heir_count` heirs
+    // can satisfy
+    pub fn validate(&self, heir_count: usize, item_count: usize) -> Result<(), String> {
+        if !self.penalty.is_finite() || self.penalty < 0.0 {
+            return Err(format!("constraint penalty must be finite and not negative ({})", self.penalty));
+        }
+
+        for assignment in self.pinned.iter().chain(self.forbidden.iter()) {
+            if assignment.item >= item_count || assignment.heir >= heir_count {
+                return Err(format!("constraint on item {} and heir {} is out of range", assignment.item, assignment.heir));
+            }
+        }
+
+        for pin in self.pinned.iter() {
+            if self.pinned.iter().any(|other| other.item == pin.item && other.heir != pin.heir) {
+                return Err(format!("item {} is pinned to several heirs", pin.item));
+            }
+
+            if self.is_forbidden(pin.item, pin.heir) {
+                return Err(format!("item {} is pinned to heir {} and forbidden for it", pin.item, pin.heir));
+            }
+        }
+
+        for item in 0..item_count {
+            if (0..heir_count).all(|heir| self.is_forbidden(item, heir)) {
+                return Err(format!("item {} is forbidden for every heir", item));
+            }
+        }
+
+        for limit in self.item_limits.iter() {
+            if limit.heir >= heir_count {
+                return Err(format!("item limit of heir {} is out of range", limit.heir));
+            }
+        }
+
+        let mut min_sum = 0;
+        let mut max_sum = 0_usize;
+
+        for heir in 0..heir_count {
+            let (min, max) = (self.min_items(heir), self.max_items(heir));
+            let pinned_count = self.pinned.iter().filter(|pin| pin.heir == heir).count();
+
+            if min > max {
+                return Err(format!("heir {} needs at least {} and at most {} items", heir, min, max));
+            }
+
+            if pinned_count > max {
+                return Err(format!("heir {} has {} pinned items but at most {} items", heir, pinned_count, max));
+            }
+
+            min_sum += min;
+            max_sum = max_sum.saturating_add(max);
+        }
+
+        if min_sum > item_count {
+            return Err(format!("heirs need at least {} items but there are {}", min_sum, item_count));
+        }
+
+        if max_sum < item_count {
+            return Err(format!("heirs take at most {} items but there are {}", max_sum, item_count));
+        }
+
+        Ok(())
+    }
+
+    // Empty without a gene (no best gene yet)
+    pub fn violations(&self, data: &[i32], heir_count: usize) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        if self.is_empty() || data.is_empty() {
+            return violations;
+        }
+
+        for pin in self.pinned.iter() {
+            let actual = match data.get(pin.item) {
+                Some(actual) => *actual as usize,
+                None => return Vec::new(),
+            };
+
+            if actual != pin.heir {
+                violations.push(Violation::Pinned { item: pin.item, heir: pin.heir, actual });
+            }
+        }
+
+        for forbidden in self.forbidden.iter() {
+            if data.get(forbidden.item).is_some_and(|heir| *heir as usize == forbidden.heir) {
+                violations.push(Violation::Forbidden { item: forbidden.item, heir: forbidden.heir });
+            }
+        }
+
+        if !self.item_limits.is_empty() {
+            let counts = item_counts(data, heir_count);
+
+            for (heir, count) in counts.into_iter().enumerate() {
+                let (min, max) = (self.min_items(heir), self.max_items(heir));
+
+                if count < min {
+                    violations.push(Violation::TooFewItems { heir, count, min });
+                } else if count > max {
+                    violations.push(Violation::TooManyItems { heir, count, max });
+                }
+            }
+        }
+
+        violations
+    }
+
+    // Units of the penalty of a gene
+    pub fn violation_amount(&self, data: &[i32], heir_count: usize) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        self.violations(data, heir_count).iter().map(|violation| violation.amount()).sum()
+    }
+
+    // Moves as few items as it can to satisfy the rules, in their order:
+    // pinned items, forbidden heirs, then the item limits. Nothing is done
+    // with `ConstraintHandling::Penalty`.
+    pub fn repair(&self, gene: &mut Gene, heir_count: usize, rng: &mut ChaCha8Rng) {
+        if self.is_empty() || self.handling != ConstraintHandling::Repair {
+            return;
+        }
+
+        for pin in self.pinned.iter() {
+            gene.set_allele(pin.item, pin.heir as i32);
+        }
+
+        for forbidden in self.forbidden.iter() {
+            if gene.data[forbidden.item] as usize != forbidden.heir {
+                continue;
+            }
+
+            let heirs: Vec<usize> = (0..heir_count).filter(|heir| self.is_allowed(forbidden.item, *heir)).collect();
+
+            if let Some(heir) = heirs.choose(rng) {
+                gene.set_allele(forbidden.item, *heir as i32);
+            }
+        }
+
+        if self.item_limits.is_empty() {
+            return;
+        }
+
+        let mut counts = item_counts(&gene.data, heir_count);
+
+        // Extra items go to the heirs below their minimum first, then to any
+        // heir below its maximum
+        for heir in 0..heir_count {
+            while counts[heir] > self.max_items(heir) {
+                let moves: Vec<(usize, usize)> = self.moves_from(gene, heir, &counts, true);
+                let moves = match moves.is_empty() {
+                    true => self.moves_from(gene, heir, &counts, false),
+                    false => moves,
+                };
+
+                match moves.choose(rng) {
+                    Some((item, to)) => {
+                        gene.set_allele(*item, *to as i32);
+                        counts[heir] -= 1;
+                        counts[*to] += 1;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        // Missing items are taken from the heirs above their minimum
+        for heir in 0..heir_count {
+            while counts[heir] < self.min_items(heir) {
+                let items: Vec<usize> = (0..gene.data.len())
+                    .filter(|item| {
+                        let from = gene.data[*item] as usize;
+                        from != heir && counts[from] > self.min_items(from) && self.is_allowed(*item, heir)
+                    })
+                    .collect();
+
+                if items.is_empty() {
+                    break;
+                }
+
+                let item = items[rng.gen_range(0..items.len())];
+                let from = gene.data[item] as usize;
+
+                gene.set_allele(item, heir as i32);
+                counts[from] -= 1;
+                counts[heir] += 1;
+            }
+        }
+    }
+
+    // (item, heir) moves of an item of `from` to another heir with room left
+    fn moves_from(&self, gene: &Gene, from: usize, counts: &[usize], below_min: bool) -> Vec<(usize, usize)> {
+        let mut moves = Vec::new();
+
+        for (item, heir) in gene.data.iter().enumerate() {
+            if *heir as usize != from {
+                continue;
+            }
+
+            for (to, count) in counts.iter().enumerate() {
+                let has_room = match below_min {
+                    true => *count < self.min_items(to),
+                    false => *count < self.max_items(to),
+                };
+
+                if to != from && has_room && self.is_allowed(item, to) {
+                    moves.push((item, to));
+                }
+            }
+        }
+
+        moves
+    }
+}
+
+// Number of items of every heir. Alleles out of range are left out.
+pub fn item_counts(data: &[i32], heir_count: usize) -> Vec<usize> {
+    let mut counts = vec![0; heir_count];
+
+    for heir in data.iter() {
+        if let Some(count) = counts.get_mut(*heir as usize) {
+            *count += 1;
+        }
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::SeedableRng;
+
+    use super::*;
+    use crate::dataset::load_problem_file;
+    use crate::problem::Problem;
+
+    #[test]
+    fn validate_refuses_bad_penalties() {
+        let mut problem = load_problem_file(concat!(env!("CARGO_MANIFEST_DIR"), "/property/constraints.toml")).unwrap().problem();
+
+        for penalty in [f64::NAN, f64::INFINITY, -1.0, 1e308] {
+            problem.constraints.penalty = penalty;
+            assert!(problem.validate().is_err(), "{}", penalty);
+        }
+
+        problem.constraints.penalty = 1000.0;
+        assert!(problem.validate().is_ok());
+    }
+
+    #[test]
+    fn repair_meets_every_rule_of_the_example() {
+        let problem = load_problem_file(concat!(env!("CARGO_MANIFEST_DIR"), "/property/constraints.toml")).unwrap().problem();
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+
+        assert_eq!(problem.constraints.handling, ConstraintHandling::Repair);
+        assert!(problem.validate().is_ok());
+
+        for _n in 0..1000 {
+            let mut gene = Gene::default();
+            problem.init(&mut gene, &mut rng);
+            problem.repair(&mut gene, &mut rng);
+
+            assert_eq!(problem.constraints.violations(&gene.data, problem.divide.len()), Vec::new(), "{:?}", gene.data);
+        }
+    }
+}
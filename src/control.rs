@@ -0,0 +1,244 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Serialize, Deserialize};
+
+use super::model::{GAModelTracker, LiveParameters, TrackerUpdate};
+use super::termination::StopReason;
+use super::observer::{Observer, Observers, ChannelObserver};
+
+// Commands for a fit running in the background
+//  - Step: runs that many generations, then pauses
+//  - SetParameters: the mutation and selection parameters
+#[derive(Clone, Debug)]
+pub enum Command {
+    Stop,
+    Pause,
+    Resume,
+    Step(usize),
+    SetParameters(LiveParameters),
+}
+
+// Parameters set during a run, from the generation they apply to
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParameterChange {
+    pub generation: i32,
+    pub parameters: LiveParameters,
+}
+
+// Anything that `fit_back` runs
+//  - start: resets the run before its first generation
+//  - check_termination: records the stopping criterion met, if any
+//  - save_checkpoint: after every generation and once more at the end
+pub(crate) trait Controlled {
+    fn parts_mut(&mut self) -> (&mut GAModelTracker, &mut Observers);
+    fn set_live_parameters(&mut self, parameters: &LiveParameters);
+    fn start(&mut self);
+    fn run_once(&mut self, generation: usize);
+    fn check_termination(&mut self) -> bool;
+    fn save_checkpoint(&mut self, _generations: usize, _is_final: bool) {}
+}
+
+// Runs `model` from its last finished generation up to `generations`.
+// `control` handles the commands of a fit running in the background.
+pub(crate) fn run<M: Controlled>(model: &mut M, generations: usize, mut control: Option<&mut Control>) {
+    let first_generation = {
+        let (tracker, observers) = model.parts_mut();
+
+        tracker.is_running = true;
+        tracker.stop_reason = None;
+        observers.on_start(tracker);
+
+        tracker.total_generation as usize
+    };
+
+    for generation in first_generation..generations {
+        if let Some(control) = control.as_deref_mut() {
+            if !control.wait(model) {
+                break;
+            }
+        }
+
+        model.run_once(generation);
+        model.save_checkpoint(generations, false);
+
+        if model.check_termination() {
+            break;
+        }
+    }
+
+    let (tracker, _observers) = model.parts_mut();
+    tracker.stop_reason.get_or_insert(StopReason::Generations);
+    tracker.is_running = false;
+    tracker.is_paused = false;
+
+    model.save_checkpoint(generations, true);
+
+    let (tracker, observers) = model.parts_mut();
+    observers.on_finish(tracker);
+}
+
+// Runs a copy of `source` in its own thread. The observers of `source` go
+// along with the run.
+pub(crate) fn spawn<M: Controlled + Clone + Send + 'static>(source: &mut M, generations: usize, resume: bool) -> (JoinHandle<()>, Sender<Command>, Receiver<TrackerUpdate>) {
+    let (tx, rx) = mpsc::channel::<TrackerUpdate>();
+    let (app_tx, app_rx) = mpsc::channel::<Command>();
+    let mut control = Control::new(app_rx);
+    let mut model = source.clone();
+
+    let observers = model.parts_mut().1;
+    *observers = std::mem::take(source.parts_mut().1);
+    observers.add(ChannelObserver::new(tx, Duration::from_millis(50)));
+
+    let handler = thread::spawn(move || {
+        if !resume {
+            model.start();
+        }
+
+        run(&mut model, generations, Some(&mut control));
+    });
+
+    (handler, app_tx, rx)
+}
+
+// Command handling of the worker thread
+pub(crate) struct Control {
+    receiver: Receiver<Command>,
+    // Generations left before pausing (None: not paused)
+    steps: Option<usize>,
+}
+
+impl Control {
+    pub(crate) fn new(receiver: Receiver<Command>) -> Self {
+        Self {
+            receiver,
+            steps: None,
+        }
+    }
+
+    // Handles the pending commands and blocks while paused. Returns false
+    // when the run has to stop.
+    pub(crate) fn wait<M: Controlled>(&mut self, model: &mut M) -> bool {
+        loop {
+            let command = if self.steps == Some(0) {
+                let (tracker, observers) = model.parts_mut();
+
+                if !tracker.is_paused {
+                    tracker.is_paused = true;
+                    observers.on_pause(tracker);
+                }
+
+                match self.receiver.recv() {
+                    Ok(command) => command,
+                    // Nobody is left to resume the run
+                    Err(_err) => Command::Stop,
+                }
+            } else {
+                match self.receiver.try_recv() {
+                    Ok(command) => command,
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+                }
+            };
+
+            match command {
+                Command::Stop => {
+                    model.parts_mut().0.stop_reason = Some(StopReason::Stopped);
+                    return false;
+                }
+                Command::Pause => self.steps = Some(0),
+                Command::Resume => self.steps = None,
+                Command::Step(generations) => self.steps = Some(generations),
+                Command::SetParameters(parameters) => {
+                    model.set_live_parameters(&parameters);
+
+                    let (tracker, observers) = model.parts_mut();
+                    let change = ParameterChange {
+                        generation: tracker.total_generation,
+                        parameters,
+                    };
+
+                    tracker.parameter_changes.push(change.clone());
+                    observers.on_parameters_changed(&change, tracker);
+                }
+            }
+        }
+
+        if let Some(steps) = self.steps.as_mut() {
+            *steps -= 1;
+        }
+        model.parts_mut().0.is_paused = false;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::GAModel;
+    use crate::problem::PropertyDistribution;
+
+    fn model() -> GAModel<PropertyDistribution> {
+        GAModel::new(PropertyDistribution::new(vec![0.5; 2], vec![1, 2, 3]))
+    }
+
+    #[test]
+    fn wait_runs_step_generations_then_pauses() {
+        let (sender, receiver) = mpsc::channel::<Command>();
+        let mut control = Control::new(receiver);
+        let mut model = model();
+
+        assert!(control.wait(&mut model));
+
+        sender.send(Command::Step(2)).unwrap();
+        assert!(control.wait(&mut model));
+        assert!(control.wait(&mut model));
+        assert!(!model.tracker.is_paused);
+
+        // The third generation waits for the next command
+        sender.send(Command::Stop).unwrap();
+        assert!(!control.wait(&mut model));
+        assert!(model.tracker.is_paused);
+        assert_eq!(model.tracker.stop_reason, Some(StopReason::Stopped));
+    }
+
+    #[test]
+    fn wait_resumes_a_paused_run() {
+        let (sender, receiver) = mpsc::channel::<Command>();
+        let mut control = Control::new(receiver);
+        let mut model = model();
+
+        sender.send(Command::Pause).unwrap();
+        sender.send(Command::Resume).unwrap();
+        assert!(control.wait(&mut model));
+        assert!(!model.tracker.is_paused);
+        assert_eq!(model.tracker.stop_reason, None);
+
+        // Nobody is left to resume the run
+        sender.send(Command::Pause).unwrap();
+        drop(sender);
+        assert!(!control.wait(&mut model));
+        assert_eq!(model.tracker.stop_reason, Some(StopReason::Stopped));
+    }
+
+    #[test]
+    fn wait_records_parameter_changes() {
+        let (sender, receiver) = mpsc::channel::<Command>();
+        let mut control = Control::new(receiver);
+        let mut model = model();
+
+        let mut parameters = model.live_parameters();
+        parameters.mutation_rate = 0.25;
+        sender.send(Command::SetParameters(parameters.clone())).unwrap();
+        assert!(control.wait(&mut model));
+
+        assert_eq!(model.mutation_rate, 0.25);
+        assert_eq!(model.tracker.parameter_changes, vec![ParameterChange { generation: 0, parameters }]);
+    }
+}
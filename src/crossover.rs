@@ -0,0 +1,65 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+
+use super::model::Gene;
+
+pub trait CrossoverOperator: Sync {
+    fn crossover(&self, parent1: &Gene, parent2: &Gene, rng: &mut ChaCha8Rng) -> (Gene, Gene);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrossoverKind {
+    OnePoint,
+    TwoPoint,
+    KPoint,
+    Uniform,
+}
+
+named_enum!(CrossoverKind, "crossover operator", {
+    OnePoint => "one-point",
+    TwoPoint => "two-point",
+    KPoint => "k-point",
+    Uniform => "uniform",
+});
+
+pub struct OnePoint;
+
+impl CrossoverOperator for OnePoint {
+    fn crossover(&self, parent1: &Gene, parent2: &Gene, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
+        parent1.one_point_crossover(parent2, rng)
+    }
+}
+
+pub struct TwoPoint;
+
+impl CrossoverOperator for TwoPoint {
+    fn crossover(&self, parent1: &Gene, parent2: &Gene, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
+        parent1.crossover(parent2, rng)
+    }
+}
+
+pub struct KPoint {
+    pub k: usize,
+}
+
+impl CrossoverOperator for KPoint {
+    fn crossover(&self, parent1: &Gene, parent2: &Gene, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
+        parent1.k_point_crossover(parent2, self.k, rng)
+    }
+}
+
+pub struct Uniform {
+    pub bias: f64,
+}
+
+impl CrossoverOperator for Uniform {
+    fn crossover(&self, parent1: &Gene, parent2: &Gene, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
+        parent1.uniform_crossover(parent2, self.bias, rng)
+    }
+}
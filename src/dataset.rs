@@ -0,0 +1,520 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+
+use super::problem::PropertyDistribution;
+use super::constraint::{Assignment, ConstraintHandling, Constraints, ItemLimit};
+
+#[derive(Debug)]
+pub enum DatasetError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    Empty {
+        path: PathBuf,
+    },
+    NegativeProperty {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        value: i32,
+    },
+    Format {
+        path: PathBuf,
+        message: String,
+    },
+    NegativeItem {
+        path: PathBuf,
+        item: String,
+        value: i32,
+    },
+    UnknownHeir {
+        path: PathBuf,
+        item: String,
+        heir: String,
+    },
+    TotalOverflow {
+        path: PathBuf,
+    },
+    ZeroTotal {
+        path: PathBuf,
+    },
+    NonFiniteShare {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatasetError::Io { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
+            DatasetError::Parse { path, line, column, token } => {
+                write!(f, "{}:{}:{}: cannot parse '{}'", path.display(), line, column, token)
+            }
+            DatasetError::Empty { path } => {
+                write!(f, "{}: file has no values", path.display())
+            }
+            DatasetError::NegativeProperty { path, line, column, value } => {
+                write!(f, "{}:{}:{}: negative property value {}", path.display(), line, column, value)
+            }
+            DatasetError::Format { path, message } => {
+                write!(f, "{}: {}", path.display(), message)
+            }
+            DatasetError::NegativeItem { path, item, value } => {
+                write!(f, "{}: item '{}' has negative value {}", path.display(), item, value)
+            }
+            DatasetError::UnknownHeir { path, item, heir } => {
+                write!(f, "{}: item '{}' names unknown heir '{}'", path.display(), item, heir)
+            }
+            DatasetError::TotalOverflow { path } => {
+                write!(f, "{}: property values sum to more than {}", path.display(), i32::MAX)
+            }
+            DatasetError::ZeroTotal { path } => {
+                write!(f, "{}: property values sum to 0", path.display())
+            }
+            DatasetError::NonFiniteShare { path, line, column } => {
+                write!(f, "{}:{}:{}: share is not a finite number", path.display(), line, column)
+            }
+        }
+    }
+}
+
+impl Error for DatasetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DatasetError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Values are separated by any whitespace, including newlines
+pub fn load_dataset(divide_path: &str, properties_path: &str) -> Result<(Vec<f64>, Vec<i32>), DatasetError> {
+    let mut divide = Vec::<f64>::new();
+
+    for (value, line, column) in read_values::<f64>(Path::new(divide_path))? {
+        if !value.is_finite() {
+            return Err(DatasetError::NonFiniteShare { path: divide_path.into(), line, column });
+        }
+
+        divide.push(value);
+    }
+
+    let mut properties = Vec::<i32>::new();
+
+    for (value, line, column) in read_values::<i32>(Path::new(properties_path))? {
+        if value < 0 {
+            return Err(DatasetError::NegativeProperty { path: properties_path.into(), line, column, value });
+        }
+
+        properties.push(value);
+    }
+
+    match total_value(&properties) {
+        None => return Err(DatasetError::TotalOverflow { path: properties_path.into() }),
+        Some(0) => return Err(DatasetError::ZeroTotal { path: properties_path.into() }),
+        Some(_total) => {}
+    }
+
+    Ok((divide, properties))
+}
+
+// Problem file (.toml or .json)
+//
+//   [metadata]
+//   name = "..."
+//
+//   [[heirs]]
+//   name = "..."
+//   share = 0.5
+//   min_items = 1        (optional)
+//   max_items = 5        (optional)
+//
+//   [[items]]
+//   name = "..."
+//   value = 100
+//   heir = "..."         (optional, pinned)
+//   forbidden = ["..."]  (optional)
+//
+//   [constraints]        (optional)
+//   handling = "repair"  (or "penalty")
+//   penalty = 1.0
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProblemFile {
+    #[serde(default)]
+    pub metadata: ProblemMetadata,
+    pub heirs: Vec<Heir>,
+    pub items: Vec<Item>,
+    #[serde(default)]
+    pub constraints: ConstraintSettings,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProblemMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Heir {
+    pub name: String,
+    pub share: f64,
+    #[serde(default)]
+    pub min_items: Option<usize>,
+    #[serde(default)]
+    pub max_items: Option<usize>,
+}
+
+// `heir` and `forbidden` are heir names
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Item {
+    pub name: String,
+    pub value: i32,
+    #[serde(default)]
+    pub heir: Option<String>,
+    #[serde(default)]
+    pub forbidden: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConstraintSettings {
+    pub handling: ConstraintHandling,
+    pub penalty: f64,
+}
+
+impl Default for ConstraintSettings {
+    fn default() -> Self {
+        let constraints = Constraints::default();
+
+        Self {
+            handling: constraints.handling,
+            penalty: constraints.penalty,
+        }
+    }
+}
+
+impl ProblemFile {
+    pub fn divide(&self) -> Vec<f64> {
+        self.heirs.iter().map(|heir| heir.share).collect()
+    }
+
+    pub fn properties(&self) -> Vec<i32> {
+        self.items.iter().map(|item| item.value).collect()
+    }
+
+    pub fn problem(&self) -> PropertyDistribution {
+        PropertyDistribution::new(self.divide(), self.properties())
+            .with_names(
+                self.heirs.iter().map(|heir| heir.name.clone()).collect(),
+                self.items.iter().map(|item| item.name.clone()).collect(),
+            )
+            .with_constraints(self.constraints())
+    }
+
+    // Heir names that match no heir are left out (`load_problem_file`
+    // rejects them)
+    pub fn constraints(&self) -> Constraints {
+        let heir_index = |name: &String| self.heirs.iter().position(|heir| heir.name == *name);
+        let mut constraints = Constraints {
+            handling: self.constraints.handling,
+            penalty: self.constraints.penalty,
+            ..Constraints::default()
+        };
+
+        for (item, entry) in self.items.iter().enumerate() {
+            if let Some(heir) = entry.heir.as_ref().and_then(heir_index) {
+                constraints.pinned.push(Assignment { item, heir });
+            }
+
+            for heir in entry.forbidden.iter().filter_map(heir_index) {
+                constraints.forbidden.push(Assignment { item, heir });
+            }
+        }
+
+        for (heir, entry) in self.heirs.iter().enumerate() {
+            if entry.min_items.is_some() || entry.max_items.is_some() {
+                constraints.item_limits.push(ItemLimit { heir, min: entry.min_items, max: entry.max_items });
+            }
+        }
+
+        constraints
+    }
+}
+
+// The format is given by the file extension
+pub fn load_problem_file(path: &str) -> Result<ProblemFile, DatasetError> {
+    let path = Path::new(path);
+    let contents = fs::read_to_string(path).map_err(|source| DatasetError::Io { path: path.into(), source })?;
+
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+
+    let problem_file: ProblemFile = match extension.as_str() {
+        "toml" => toml::from_str(&contents).map_err(|err| DatasetError::Format { path: path.into(), message: err.to_string() })?,
+        "json" => serde_json::from_str(&contents).map_err(|err| DatasetError::Format { path: path.into(), message: err.to_string() })?,
+        _ => {
+            return Err(DatasetError::Format { path: path.into(), message: "unknown problem format (expected .toml or .json)".to_string() });
+        }
+    };
+
+    if problem_file.heirs.is_empty() || problem_file.items.is_empty() {
+        return Err(DatasetError::Empty { path: path.into() });
+    }
+
+    if let Some(item) = problem_file.items.iter().find(|item| item.value < 0) {
+        return Err(DatasetError::NegativeItem { path: path.into(), item: item.name.clone(), value: item.value });
+    }
+
+    if let Some(heir) = problem_file.heirs.iter().find(|heir| !heir.share.is_finite()) {
+        return Err(DatasetError::Format { path: path.into(), message: format!("heir '{}' has share {}", heir.name, heir.share) });
+    }
+
+    match total_value(&problem_file.properties()) {
+        None => return Err(DatasetError::TotalOverflow { path: path.into() }),
+        Some(0) => return Err(DatasetError::ZeroTotal { path: path.into() }),
+        Some(_total) => {}
+    }
+
+    for item in problem_file.items.iter() {
+        let unknown = item.heir.iter()
+            .chain(item.forbidden.iter())
+            .find(|name| !problem_file.heirs.iter().any(|heir| heir.name == **name));
+
+        if let Some(name) = unknown {
+            return Err(DatasetError::UnknownHeir { path: path.into(), item: item.name.clone(), heir: name.clone() });
+        }
+    }
+
+    Ok(problem_file)
+}
+
+// Sum of the property values, None when it does not fit the i32 totals of
+// `PropertyDistribution`
+pub fn total_value(properties: &[i32]) -> Option<i32> {
+    properties.iter().try_fold(0_i32, |sum, value| sum.checked_add(*value))
+}
+
+// Parses every whitespace separated token with its 1-based line and column
+fn read_values<T: FromStr>(path: &Path) -> Result<Vec<(T, usize, usize)>, DatasetError> {
+    let contents = fs::read_to_string(path).map_err(|source| DatasetError::Io { path: path.into(), source })?;
+
+    let mut values = Vec::<(T, usize, usize)>::new();
+
+    for (line_index, text) in contents.lines().enumerate() {
+        let mut rest = text;
+
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            let token_rest = &rest[start..];
+            let end = token_rest.find(char::is_whitespace).unwrap_or(token_rest.len());
+            let token = &token_rest[..end];
+
+            let line = line_index + 1;
+            let column = text[..text.len() - token_rest.len()].chars().count() + 1;
+
+            match token.parse::<T>() {
+                Ok(value) => values.push((value, line, column)),
+                Err(_err) => {
+                    return Err(DatasetError::Parse { path: path.into(), line, column, token: token.to_string() });
+                }
+            }
+
+            rest = &token_rest[end..];
+        }
+    }
+
+    if values.is_empty() {
+        return Err(DatasetError::Empty { path: path.into() });
+    }
+
+    Ok(values)
+}
+
+// Allowed distance of the share sum from 1.0
+pub const SHARE_SUM_TOLERANCE: f64 = 1e-6;
+
+#[derive(Clone, Debug, Default)]
+pub struct DatasetReport {
+    pub share_count: usize,
+    pub expected_share_count: Option<usize>,
+    pub share_sum: f64,
+    pub negative_shares: Vec<usize>,
+    pub zero_properties: Vec<usize>,
+}
+
+impl DatasetReport {
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::<String>::new();
+
+        if let Some(expected) = self.expected_share_count {
+            if expected != self.share_count {
+                warnings.push(format!("{} shares for {} heirs", self.share_count, expected));
+            }
+        }
+
+        if !self.shares_sum_to_one() {
+            warnings.push(format!("shares sum to {} instead of 1", self.share_sum));
+        }
+
+        if !self.negative_shares.is_empty() {
+            warnings.push(format!("negative shares at {:?}", self.negative_shares));
+        }
+
+        if !self.zero_properties.is_empty() {
+            warnings.push(format!("zero-value properties at {:?}", self.zero_properties));
+        }
+
+        warnings
+    }
+
+    pub fn shares_sum_to_one(&self) -> bool {
+        (self.share_sum - 1.0).abs() <= SHARE_SUM_TOLERANCE
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.warnings().is_empty()
+    }
+}
+
+// Indices in the report are 0-based positions in the files
+pub fn validate_dataset(divide: &[f64], properties: &[i32], expected_share_count: Option<usize>) -> DatasetReport {
+    DatasetReport {
+        share_count: divide.len(),
+        expected_share_count,
+        share_sum: divide.iter().sum(),
+        negative_shares: divide.iter().enumerate().filter(|(_n, share)| **share < 0.0).map(|(n, _share)| n).collect(),
+        zero_properties: properties.iter().enumerate().filter(|(_n, value)| **value == 0).map(|(n, _value)| n).collect(),
+    }
+}
+
+// Scales the shares so that they sum to 1. Negative shares are left to the
+// validation report, since no scaling can fix them.
+pub fn normalize_shares(divide: &mut [f64]) {
+    let share_sum: f64 = divide.iter().sum();
+
+    if share_sum > 0.0 {
+        for share in divide.iter_mut() {
+            *share /= share_sum;
+        }
+    }
+}
+
+// Heir count of the bundled `divide<N>.txt` files
+pub fn expected_share_count(divide_path: &Path) -> Option<usize> {
+    divide_path.file_stem()?.to_str()?.strip_prefix("divide")?.parse::<usize>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes `contents` to a file of the temporary directory, named after the
+    // test so that tests running in parallel do not share it
+    fn temp_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("rust_genetic_algorithm-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn read_values_reports_line_and_column() {
+        let path = temp_file("values.txt", "1 2\n\n  3\t4\n");
+        let values = read_values::<i32>(Path::new(&path)).unwrap();
+        assert_eq!(values, vec![(1, 1, 1), (2, 1, 3), (3, 3, 3), (4, 3, 5)]);
+
+        let path = temp_file("parse.txt", "0.5 0.5\n 0.2 x0.3\n");
+        match read_values::<f64>(Path::new(&path)) {
+            Err(DatasetError::Parse { line, column, token, .. }) => assert_eq!((line, column, token.as_str()), (2, 6, "x0.3")),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+
+        let path = temp_file("empty.txt", " \n\t\n");
+        assert!(matches!(read_values::<i32>(Path::new(&path)), Err(DatasetError::Empty { .. })));
+
+        let missing = temp_file("missing.txt", "");
+        fs::remove_file(&missing).unwrap();
+        assert!(matches!(read_values::<i32>(Path::new(&missing)), Err(DatasetError::Io { .. })));
+
+        let divide = temp_file("divide.txt", "0.5 0.5");
+        let properties = temp_file("negative.txt", "10 20\n30  -40\n");
+        match load_dataset(&divide, &properties) {
+            Err(DatasetError::NegativeProperty { line, column, value, .. }) => assert_eq!((line, column, value), (2, 5, -40)),
+            other => panic!("expected a negative property, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn load_problem_file_reports_each_error() {
+        let heirs = "[[heirs]]\nname = \"A\"\nshare = 1.0\n";
+
+        let path = temp_file("problem.toml", &format!("{}[[items]]\nname = \"Car\"\nvalue = 10\n", heirs));
+        let problem_file = load_problem_file(&path).unwrap();
+        assert_eq!((problem_file.divide(), problem_file.properties()), (vec![1.0], vec![10]));
+
+        let path = temp_file("problem.yaml", "");
+        assert!(matches!(load_problem_file(&path), Err(DatasetError::Format { .. })));
+
+        let path = temp_file("syntax.toml", "[[heirs]\n");
+        assert!(matches!(load_problem_file(&path), Err(DatasetError::Format { .. })));
+
+        let path = temp_file("no_items.json", r#"{"heirs": [{"name": "A", "share": 1.0}], "items": []}"#);
+        assert!(matches!(load_problem_file(&path), Err(DatasetError::Empty { .. })));
+
+        let path = temp_file("negative.toml", &format!("{}[[items]]\nname = \"Debt\"\nvalue = -5\n", heirs));
+        match load_problem_file(&path) {
+            Err(DatasetError::NegativeItem { item, value, .. }) => assert_eq!((item.as_str(), value), ("Debt", -5)),
+            other => panic!("expected a negative item, got {:?}", other),
+        }
+
+        let path = temp_file("overflow.toml", &format!("{}[[items]]\nname = \"A\"\nvalue = {}\n\n[[items]]\nname = \"B\"\nvalue = 1\n", heirs, i32::MAX));
+        assert!(matches!(load_problem_file(&path), Err(DatasetError::TotalOverflow { .. })));
+
+        let path = temp_file("zero.toml", &format!("{}[[items]]\nname = \"Letter\"\nvalue = 0\n", heirs));
+        assert!(matches!(load_problem_file(&path), Err(DatasetError::ZeroTotal { .. })));
+
+        let path = temp_file("missing.toml", "");
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(load_problem_file(&path), Err(DatasetError::Io { .. })));
+    }
+
+    #[test]
+    fn load_problem_file_refuses_unknown_heirs() {
+        let heirs = "[[heirs]]\nname = \"A\"\nshare = 1.0\n";
+
+        let path = temp_file("pinned.toml", &format!("{}[[items]]\nname = \"Car\"\nvalue = 10\nheir = \"B\"\n", heirs));
+        match load_problem_file(&path) {
+            Err(DatasetError::UnknownHeir { item, heir, .. }) => assert_eq!((item.as_str(), heir.as_str()), ("Car", "B")),
+            other => panic!("expected an unknown heir, got {:?}", other),
+        }
+
+        let path = temp_file("forbidden.toml", &format!("{}[[items]]\nname = \"Car\"\nvalue = 10\nforbidden = [\"A\", \"C\"]\n", heirs));
+        match load_problem_file(&path) {
+            Err(DatasetError::UnknownHeir { heir, .. }) => assert_eq!(heir, "C"),
+            other => panic!("expected an unknown heir, got {:?}", other),
+        }
+    }
+}
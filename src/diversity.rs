@@ -0,0 +1,115 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use rand::prelude::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+
+use super::model::Gene;
+
+// Population diversity recorded every generation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiversityMetric {
+    Fitness,
+    Genotype,
+}
+
+named_enum!(DiversityMetric, "diversity metric", {
+    Fitness => "fitness",
+    Genotype => "genotype",
+});
+
+// Mean |f_i - f_j| over every ordered pair, in O(n log n) by sorting the
+// fitness values: sum_i sum_j |f_i - f_j| = 2 * sum_k (2k - n + 1) * f_k.
+// The genes can be in any order (NSGA-II sorts them by front).
+pub fn fitness_diversity(genes: &[Gene]) -> f64 {
+    let len = genes.len() as f64;
+
+    if genes.is_empty() {
+        return 0.0;
+    }
+
+    let mut fitnesses: Vec<f64> = genes.iter().map(|gene| gene.fitness).collect();
+    fitnesses.sort_by(|a, b| a.total_cmp(b));
+
+    let weighted_sum: f64 = fitnesses.iter()
+        .enumerate()
+        .map(|(k, fitness)| (2.0 * k as f64 - len + 1.0) * fitness)
+        .sum();
+
+    2.0 * weighted_sum / (len * len)
+}
+
+// Mean `Gene::compare` distance between pairs of genes, scaled to 0..=1.
+// Every pair is compared up to `samples` pairs, random pairs otherwise.
+pub fn genotype_diversity(genes: &[Gene], min_value: i32, max_value: i32, samples: usize, rng: &mut ChaCha8Rng) -> f64 {
+    let len = genes.len();
+    let data_len = genes.first().map(|gene| gene.data.len()).unwrap_or(0);
+
+    if len < 2 || data_len == 0 || samples == 0 {
+        return 0.0;
+    }
+
+    let mut total_distance: f64 = 0.0;
+    let mut pairs = 0_usize;
+
+    if len * (len - 1) / 2 <= samples {
+        for y in 0..len {
+            for x in (y + 1)..len {
+                total_distance += genes[y].compare(&genes[x], min_value, max_value) as f64;
+                pairs += 1;
+            }
+        }
+    } else {
+        for _n in 0..samples {
+            let y = rng.gen_range(0..len);
+            let x = (y + rng.gen_range(1..len)) % len;
+
+            total_distance += genes[y].compare(&genes[x], min_value, max_value) as f64;
+            pairs += 1;
+        }
+    }
+
+    // Two genes differ by at most every locus counted twice
+    total_distance / (pairs as f64 * 2.0 * data_len as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn brute_force(genes: &[Gene]) -> f64 {
+        if genes.is_empty() {
+            return 0.0;
+        }
+
+        let len = genes.len() as f64;
+        let sum: f64 = genes.iter()
+            .flat_map(|a| genes.iter().map(move |b| (a.fitness - b.fitness).abs()))
+            .sum();
+
+        sum / (len * len)
+    }
+
+    #[test]
+    fn fitness_diversity_matches_brute_force() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+        for len in [0, 1, 2, 7, 100] {
+            // Unsorted, with ties
+            let genes: Vec<Gene> = (0..len).map(|_n| Gene {
+                fitness: rng.gen_range(0..20) as f64 / 8.0,
+                ..Gene::default()
+            }).collect();
+
+            let (fast, slow) = (fitness_diversity(&genes), brute_force(&genes));
+            assert!((fast - slow).abs() < 1e-9, "{} genes: {} != {}", len, fast, slow);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+use super::model::GAModelTracker;
+
+pub fn serialize_seconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+pub fn deserialize_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    Ok(Duration::from_secs_f64(f64::deserialize(deserializer)?))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+// Exports in the format given by the file extension
+pub fn export(tracker: &GAModelTracker, path: &Path) -> io::Result<()> {
+    match ExportFormat::from_path(path) {
+        Some(ExportFormat::Json) => export_json(tracker, path),
+        Some(ExportFormat::Csv) => export_csv(tracker, path),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown export format: {} (expected .json or .csv)", path.display()),
+        )),
+    }
+}
+
+pub fn export_json(tracker: &GAModelTracker, path: &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    serde_json::to_writer_pretty(&mut writer, tracker)?;
+    writer.flush()
+}
+
+// Writes the generation series to `path` and everything else as key,value
+// rows to `<stem>_summary.csv` next to it. A Pareto front goes to
+// `<stem>_pareto.csv`.
+pub fn export_csv(tracker: &GAModelTracker, path: &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    // One more best fitness column per island
+    write!(writer, "generation,best_fitness,average_fitness,average_diff")?;
+    for island in 0..tracker.island_best_fitness_changes.len() {
+        write!(writer, ",island_{}_best_fitness", island)?;
+    }
+    writeln!(writer)?;
+
+    for generation in 0..tracker.total_generation as usize {
+        write!(
+            writer,
+            "{},{},{},{}",
+            generation,
+            tracker.best_fitness_changes[generation],
+            tracker.average_fitness_changes[generation],
+            tracker.average_diff_changes[generation],
+        )?;
+
+        for changes in &tracker.island_best_fitness_changes {
+            write!(writer, ",{}", changes[generation])?;
+        }
+        writeln!(writer)?;
+    }
+
+    writer.flush()?;
+
+    if !tracker.pareto_front.is_empty() {
+        export_pareto_csv(tracker, &pareto_path(path))?;
+    }
+
+    let mut writer = BufWriter::new(File::create(summary_path(path))?);
+
+    writeln!(writer, "key,value")?;
+    writeln!(writer, "local_datetime,{}", tracker.local_datetime.to_rfc3339())?;
+    writeln!(writer, "seed,{}", tracker.seed)?;
+    writeln!(writer, "elapsed_seconds,{}", tracker.elapsed.as_secs_f64())?;
+    writeln!(writer, "total_generation,{}", tracker.total_generation)?;
+    writeln!(writer, "best_generation,{}", tracker.best_generation)?;
+    writeln!(writer, "best_fitness,{}", tracker.best_gene.fitness)?;
+    writeln!(writer, "evaluations,{}", tracker.evaluations)?;
+
+    if let Some(stop_reason) = tracker.stop_reason {
+        writeln!(writer, "stop_reason,{}", stop_reason)?;
+    }
+
+    let gene_data: Vec<String> = tracker.best_gene.data.iter().map(|allele| allele.to_string()).collect();
+    writeln!(writer, "best_gene,{}", gene_data.join(" "))?;
+
+    // Parameters are flattened through their JSON representation
+    let parameters = [
+        serde_json::to_value(&tracker.parameters)?,
+        serde_json::to_value(&tracker.islands)?,
+    ];
+
+    for parameters in parameters {
+        if let serde_json::Value::Object(map) = parameters {
+            for (key, value) in map {
+                match value {
+                    serde_json::Value::String(value) => writeln!(writer, "{},{}", key, value)?,
+                    value => writeln!(writer, "{},{}", key, value)?,
+                }
+            }
+        }
+    }
+
+    writer.flush()
+}
+
+// One row per gene of the front: its objectives, then its data
+pub fn export_pareto_csv(tracker: &GAModelTracker, path: &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writeln!(writer, "{},gene", tracker.objective_names.join(","))?;
+
+    for gene in &tracker.pareto_front {
+        let objectives: Vec<String> = gene.objectives.iter().map(|objective| objective.to_string()).collect();
+        let gene_data: Vec<String> = gene.data.iter().map(|allele| allele.to_string()).collect();
+
+        writeln!(writer, "{},{}", objectives.join(","), gene_data.join(" "))?;
+    }
+
+    writer.flush()
+}
+
+pub fn summary_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!("{}_summary.csv", stem))
+}
+
+pub fn pareto_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!("{}_pareto.csv", stem))
+}
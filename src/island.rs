@@ -0,0 +1,331 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use rand::prelude::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+
+use super::model::{GAModel, GAModelTracker, Gene, LiveParameters, TrackerUpdate};
+use super::problem::{Problem, PropertyDistribution};
+use super::control::{self, Command, Controlled};
+use super::observer::{Observer, Observers};
+use super::log::LogEntry;
+use super::nsga::{ObjectiveMode, non_dominated_sort, pareto_front};
+
+// Islands that receive the migrants of an island
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Topology {
+    Ring,
+    Full,
+    Random,
+}
+
+named_enum!(Topology, "topology", {
+    Ring => "ring",
+    Full => "full",
+    Random => "random",
+});
+
+impl Topology {
+    //  - Ring: the next island
+    //  - Full: every other island
+    //  - Random: one other island, drawn every migration
+    fn targets(&self, island: usize, island_count: usize, rng: &mut ChaCha8Rng) -> Vec<usize> {
+        if island_count < 2 {
+            return Vec::new();
+        }
+
+        match self {
+            Topology::Ring => vec![(island + 1) % island_count],
+            Topology::Full => (0..island_count).filter(|target| *target != island).collect(),
+            Topology::Random => vec![(island + rng.gen_range(1..island_count)) % island_count],
+        }
+    }
+}
+
+// How migrants take the place of the worst genes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Replacement {
+    Worst,
+    WorstIfBetter,
+}
+
+named_enum!(Replacement, "replacement", {
+    Worst => "worst",
+    WorstIfBetter => "worst-if-better",
+});
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IslandParameters {
+    pub island_count: usize,
+    pub migration_interval: usize,
+    pub migrant_count: usize,
+    pub topology: Topology,
+    pub replacement: Replacement,
+}
+
+impl Default for IslandParameters {
+    fn default() -> Self {
+        Self {
+            island_count: 1,
+            migration_interval: 50,
+            migrant_count: 2,
+            topology: Topology::Ring,
+            replacement: Replacement::Worst,
+        }
+    }
+}
+
+// Evolves `island_count` copies of `model` in their own threads and moves the
+// best genes between them every `migration_interval` generations. The
+// observers of `model` see the islands as a whole.
+#[derive(Clone)]
+pub struct IslandModel<P: Problem = PropertyDistribution> {
+    pub model: GAModel<P>,
+    pub parameters: IslandParameters,
+    pub islands: Vec<GAModel<P>>,
+    pub tracker: GAModelTracker,
+    pub observers: Observers,
+    logs: IslandLogs,
+    rng: ChaCha8Rng,
+}
+
+// Log entries of the islands, passed on to the observers of the island model
+// after every generation
+#[derive(Clone, Default)]
+struct IslandLogs {
+    island: usize,
+    entries: Arc<Mutex<Vec<LogEntry>>>,
+}
+
+impl IslandLogs {
+    fn for_island(&self, island: usize) -> Self {
+        Self { island, entries: Arc::clone(&self.entries) }
+    }
+
+    fn take(&self) -> Vec<LogEntry> {
+        std::mem::take(&mut *self.entries.lock().unwrap())
+    }
+}
+
+impl Observer for IslandLogs {
+    fn on_log(&mut self, entry: &LogEntry) {
+        let mut entry = entry.clone();
+        entry.message = format!("island {}: {}", self.island, entry.message);
+
+        self.entries.lock().unwrap().push(entry);
+    }
+}
+
+impl<P: Problem> IslandModel<P> {
+    pub fn new(mut model: GAModel<P>, parameters: IslandParameters) -> Self {
+        let rng = ChaCha8Rng::seed_from_u64(model.seed);
+        let observers = std::mem::take(&mut model.observers);
+
+        Self {
+            model,
+            parameters,
+            islands: Vec::new(),
+            tracker: GAModelTracker::default(),
+            observers,
+            logs: IslandLogs::default(),
+            rng,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.model.validate()?;
+
+        if self.parameters.island_count == 0 {
+            return Err("island_count must be at least 1".to_string());
+        }
+
+        if self.parameters.migration_interval == 0 {
+            return Err("migration_interval must be at least 1".to_string());
+        }
+
+        if self.parameters.migrant_count >= self.model.gene_len {
+            return Err(format!("migrant_count must be less than gene_len ({})", self.parameters.migrant_count));
+        }
+
+        Ok(())
+    }
+
+    pub fn fit_back(&mut self, generations: usize) -> (thread::JoinHandle<()>, mpsc::Sender<Command>, mpsc::Receiver<TrackerUpdate>) {
+        control::spawn(self, generations, false)
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        self.start();
+        control::run(self, generations, None);
+    }
+
+    // The stopping criteria of `model` apply to the islands as a whole
+    fn check_termination(&mut self) -> bool {
+        self.tracker.stop_reason = self.model.termination.check(&self.tracker);
+        self.tracker.stop_reason.is_some()
+    }
+
+    fn start(&mut self) {
+        self.rng = ChaCha8Rng::seed_from_u64(self.model.seed);
+
+        self.tracker.reset();
+        self.tracker.seed = self.model.seed;
+        self.tracker.parameters = Some(self.model.parameters());
+        self.tracker.islands = Some(self.parameters.clone());
+        self.tracker.objective_names = self.model.problem.objective_names();
+
+        // Island seeds come from the model seed
+        self.islands = (0..self.parameters.island_count).map(|n| {
+            let mut island = self.model.clone();
+            island.seed = self.rng.gen();
+            island.observers.add(self.logs.for_island(n));
+            island.start();
+            island
+        }).collect();
+
+        self.tracker.island_best_fitness_changes = vec![Vec::new(); self.islands.len()];
+    }
+
+    fn run_once(&mut self, generation: usize) {
+        let now = Instant::now();
+
+        // Islands do not share anything between migrations, so the thread
+        // timing never changes the results
+        thread::scope(|scope| {
+            for island in self.islands.iter_mut() {
+                scope.spawn(move || island.run_once(generation));
+            }
+        });
+
+        if (generation + 1).is_multiple_of(self.parameters.migration_interval) {
+            self.migrate();
+        }
+
+        for entry in self.logs.take() {
+            self.observers.on_log(&entry);
+        }
+
+        let island_count = self.islands.len() as f64;
+        self.tracker.evaluations = self.islands.iter().map(|island| island.tracker.evaluations).sum();
+
+        // No best gene yet before the first generation, whatever the metric
+        let previous_best_fitness = match self.tracker.best_gene.data.is_empty() {
+            true => None,
+            false => Some(self.tracker.best_gene.fitness),
+        };
+        let mut has_new_best = false;
+        let mut best_fitness = f64::MAX;
+        let mut average_fitness = 0.0;
+        let mut average_diff = 0.0;
+
+        for (n, island) in self.islands.iter().enumerate() {
+            let island_best_fitness = island.tracker.best_fitness_changes.last().copied().unwrap_or(f64::MAX);
+
+            best_fitness = best_fitness.min(island_best_fitness);
+            average_fitness += island.tracker.average_fitness_changes.last().copied().unwrap_or(0.0) / island_count;
+            average_diff += island.tracker.average_diff_changes.last().copied().unwrap_or(0.0) / island_count;
+
+            self.tracker.island_best_fitness_changes[n].push(island_best_fitness);
+
+            if self.tracker.best_gene.data.is_empty() || island.tracker.best_gene.fitness < self.tracker.best_gene.fitness {
+                self.tracker.best_gene = island.tracker.best_gene.clone();
+                self.tracker.best_generation = self.tracker.total_generation;
+                has_new_best = true;
+            }
+        }
+
+        // Non-dominated genes of every island front
+        if self.model.objective_mode == ObjectiveMode::Nsga2 {
+            let genes: Vec<Gene> = self.islands.iter().flat_map(|island| island.tracker.pareto_front.iter().cloned()).collect();
+            let points: Vec<&[f64]> = genes.iter().map(|gene| gene.objectives.as_slice()).collect();
+            let fronts = non_dominated_sort(&points);
+
+            self.tracker.set_pareto_front(pareto_front(&genes, &fronts));
+        }
+
+        if has_new_best {
+            let diff = previous_best_fitness.map(|fitness| self.tracker.best_gene.fitness - fitness).unwrap_or(0.0);
+            self.observers.on_new_best(generation, diff, &self.tracker);
+        }
+
+        self.tracker.append(best_fitness, average_fitness, average_diff);
+        self.tracker.elapsed += now.elapsed();
+
+        self.observers.on_generation_end(generation, &self.tracker);
+    }
+
+    // Copies the best `migrant_count` genes of every island to its targets.
+    // Migrants are taken before any island is changed.
+    fn migrate(&mut self) {
+        let migrant_count = self.parameters.migrant_count;
+
+        for island in self.islands.iter_mut() {
+            island.set_fitnesses();
+        }
+
+        let migrants: Vec<Vec<Gene>> = self.islands.iter()
+            .map(|island| island.genes.iter().take(migrant_count).cloned().collect())
+            .collect();
+
+        let island_count = self.islands.len();
+        let mut arrivals: Vec<Vec<Gene>> = vec![Vec::new(); island_count];
+
+        for (source, genes) in migrants.into_iter().enumerate() {
+            for target in self.parameters.topology.targets(source, island_count, &mut self.rng) {
+                arrivals[target].extend(genes.iter().cloned());
+            }
+        }
+
+        for (island, mut arrival) in self.islands.iter_mut().zip(arrivals) {
+            arrival.sort_by(|a, b| a.fitness.total_cmp(&b.fitness));
+
+            // The best gene of an island is never replaced
+            let replace_len = arrival.len().min(island.genes.len().saturating_sub(1));
+            let len = island.genes.len();
+
+            for (n, migrant) in arrival.into_iter().take(replace_len).enumerate() {
+                let worst = &mut island.genes[len - 1 - n];
+
+                if self.parameters.replacement == Replacement::Worst || migrant.fitness < worst.fitness {
+                    *worst = migrant;
+                }
+            }
+        }
+    }
+}
+
+impl<P: Problem> Controlled for IslandModel<P> {
+    fn parts_mut(&mut self) -> (&mut GAModelTracker, &mut Observers) {
+        (&mut self.tracker, &mut self.observers)
+    }
+
+    // Islands keep their own seeds
+    fn set_live_parameters(&mut self, parameters: &LiveParameters) {
+        self.model.set_live_parameters(parameters);
+
+        for island in self.islands.iter_mut() {
+            island.set_live_parameters(parameters);
+        }
+    }
+
+    fn start(&mut self) {
+        IslandModel::start(self);
+    }
+
+    fn run_once(&mut self, generation: usize) {
+        IslandModel::run_once(self, generation);
+    }
+
+    fn check_termination(&mut self) -> bool {
+        IslandModel::check_termination(self)
+    }
+}
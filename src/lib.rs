@@ -3,36 +3,85 @@
 // 
 // YeJun Jung (yejun614@naver.com)
 
-use std::fs;
 use std::io::{stdout, stdin, Write};
 
+// `ALL`, `Display` and `FromStr` of an enum of unit variants, from the name
+// of each variant. Names are parsed case-insensitively, and `what` tells
+// which kind of value an unknown name was meant to be.
+macro_rules! named_enum {
+    ($name:ident, $what:literal, { $($variant:ident => $text:literal),+ $(,)? }) => {
+        impl $name {
+            pub const ALL: [$name; [$($text),+].len()] = [$($name::$variant),+];
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let name = match self {
+                    $($name::$variant => $text),+
+                };
+
+                write!(f, "{}", name)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::ALL.iter()
+                    .find(|value| value.to_string() == s.trim().to_lowercase())
+                    .copied()
+                    .ok_or(format!("unknown {}: {}", $what, s))
+            }
+        }
+    };
+}
+
 pub mod model;
 
-pub mod app;
+pub mod dataset;
 
-pub fn load_dataset(divide_path: &str, properties_path: &str) -> (Vec<f64>, Vec<i32>) {
-    let divide_contents = fs::read_to_string(divide_path).expect(" [ERROR] File read error");
-    let properties_contents = fs::read_to_string(properties_path).expect(" [ERROR] File read error");
+pub mod problem;
 
-    let mut divide = Vec::<f64>::new();
+pub mod crossover;
 
-    for val in divide_contents.split(" ") {
-        divide.push(val.parse::<f64>().unwrap());
-    }
+pub mod mutation;
 
-    let mut properties = Vec::<i32>::new();
+pub mod export;
 
-    for val in properties_contents.split(" ") {
-        properties.push(val.parse::<i32>().unwrap());
-    }
+#[cfg(feature = "plot")]
+pub mod plot;
 
-    (divide, properties)
-}
+pub mod selection;
+
+pub mod diversity;
+
+pub mod island;
+
+pub mod checkpoint;
+
+pub mod termination;
+
+pub mod control;
+
+pub mod observer;
+
+pub mod log;
+
+pub mod nsga;
+
+pub mod metric;
+
+pub mod constraint;
+
+pub mod app;
+
+pub use dataset::{load_dataset, load_problem_file, validate_dataset, normalize_shares, DatasetError, DatasetReport, ProblemFile};
 
 pub fn read_line_with_default<T>(message: &str, read: &mut T, default: T) where
     T: Clone + std::fmt::Display + std::str::FromStr
 {
-    print!(" | {} ({}) > ", message, default.to_string());
+    print!(" | {} ({}) > ", message, default);
 
     let mut read_str = String::new();
 
@@ -0,0 +1,132 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+
+use chrono::{Local, DateTime};
+
+use super::model::GAModelTracker;
+use super::control::ParameterChange;
+use super::observer::Observer;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 3] = [
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// One message of a run, tagged with the generation it happened in
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    pub level: LogLevel,
+    pub generation: i32,
+    pub message: String,
+}
+
+impl LogEntry {
+    pub fn new(level: LogLevel, generation: i32, message: String) -> Self {
+        Self {
+            time: Local::now(),
+            level,
+            generation,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} [{}] generation {}: {}", self.time.format("%Y-%m-%d %H:%M:%S%.3f"), self.level, self.generation, self.message)
+    }
+}
+
+// One entry per line
+pub fn save_logs<'a>(entries: impl IntoIterator<Item = &'a LogEntry>, path: &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    for entry in entries {
+        writeln!(writer, "{}", entry)?;
+    }
+
+    writer.flush()
+}
+
+// Turns the events of a run into log entries for another thread. A closed
+// receiver is ignored.
+pub struct LogObserver {
+    sender: Sender<LogEntry>,
+}
+
+impl LogObserver {
+    pub fn new(sender: Sender<LogEntry>) -> Self {
+        Self { sender }
+    }
+
+    fn send(&self, level: LogLevel, generation: i32, message: String) {
+        self.sender.send(LogEntry::new(level, generation, message)).ok();
+    }
+}
+
+impl Observer for LogObserver {
+    fn on_start(&mut self, tracker: &GAModelTracker) {
+        let message = match tracker.total_generation {
+            0 => format!("Fit started (seed {})", tracker.seed),
+            generation => format!("Fit resumed at generation {} (seed {})", generation, tracker.seed),
+        };
+
+        self.send(LogLevel::Info, tracker.total_generation, message);
+    }
+
+    fn on_new_best(&mut self, generation: usize, improvement: f64, tracker: &GAModelTracker) {
+        self.send(LogLevel::Info, generation as i32, format!("New best fitness: {}, diffence: {}", tracker.best_gene.fitness, improvement));
+    }
+
+    fn on_finish(&mut self, tracker: &GAModelTracker) {
+        let message = match tracker.stop_reason {
+            Some(stop_reason) => format!("Fit done ({})", stop_reason),
+            None => "Fit done".to_string(),
+        };
+
+        self.send(LogLevel::Info, tracker.total_generation, message);
+    }
+
+    fn on_pause(&mut self, tracker: &GAModelTracker) {
+        self.send(LogLevel::Info, tracker.total_generation, "Paused".to_string());
+    }
+
+    fn on_parameters_changed(&mut self, change: &ParameterChange, _tracker: &GAModelTracker) {
+        self.send(LogLevel::Info, change.generation, "Mutation and selection parameters changed".to_string());
+    }
+
+    fn on_log(&mut self, entry: &LogEntry) {
+        self.sender.send(entry.clone()).ok();
+    }
+}
@@ -3,23 +3,306 @@
 // 
 // YeJun Jung (yejun614@naver.com)
 
-use std::env;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use clap::{Args, Parser, Subcommand};
 
 use rust_genetic_algorithm::*;
 use rust_genetic_algorithm::app::GeneApp;
-use rust_genetic_algorithm::model::GAModel;
+use rust_genetic_algorithm::export::export;
+#[cfg(feature = "plot")]
+use rust_genetic_algorithm::plot::{PlotOptions, PlotSeries};
+use rust_genetic_algorithm::model::{GAModel, GAModelTracker};
+use rust_genetic_algorithm::checkpoint::Checkpoint;
+use rust_genetic_algorithm::termination::Termination;
+use rust_genetic_algorithm::observer::ConsoleObserver;
+use rust_genetic_algorithm::nsga::ObjectiveMode;
+use rust_genetic_algorithm::metric::FitnessMetric;
+use rust_genetic_algorithm::problem::{Problem, PropertyDistribution};
+use rust_genetic_algorithm::constraint::ConstraintHandling;
+use rust_genetic_algorithm::selection::SelectionKind;
+use rust_genetic_algorithm::crossover::CrossoverKind;
+use rust_genetic_algorithm::mutation::{MutationKind, MutationSchedule};
+use rust_genetic_algorithm::diversity::DiversityMetric;
+use rust_genetic_algorithm::island::{IslandModel, IslandParameters, Topology, Replacement};
+
+// Exit codes of the batch mode
+const EXIT_INVALID_PARAMETERS: u8 = 2;
+const EXIT_DATASET_ERROR: u8 = 3;
+const EXIT_EXPORT_ERROR: u8 = 4;
+const EXIT_DATASET_WARNING: u8 = 5;
+const EXIT_CHECKPOINT_ERROR: u8 = 6;
+
+const DEFAULT_GENERATIONS: usize = 5000;
+
+#[derive(Parser)]
+#[command(about = "Genetic algorithm example (property distribution problem)")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Interactive console mode (GUI mode otherwise)
+    #[arg(long)]
+    console: bool,
+
+    /// Problem file of the console mode (.toml or .json)
+    #[arg(long, requires = "console")]
+    problem: Option<PathBuf>,
+
+    /// Continue the console mode run saved in a checkpoint file
+    #[arg(long, requires = "console")]
+    resume: Option<PathBuf>,
+
+    /// Save a checkpoint of the console mode run to this file
+    #[arg(long, requires = "console")]
+    checkpoint: Option<PathBuf>,
+
+    #[command(flatten)]
+    operators: OperatorArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fit without any prompt and exit
+    Run(RunArgs),
+}
+
+// Operators that skip their prompt in the interactive console mode
+#[derive(Args)]
+struct OperatorArgs {
+    #[arg(long)]
+    selection: Option<SelectionKind>,
+
+    #[arg(long)]
+    crossover: Option<CrossoverKind>,
+
+    #[arg(long)]
+    mutation: Option<MutationKind>,
+
+    #[arg(long)]
+    mutation_schedule: Option<MutationSchedule>,
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Problem file with named heirs and items (.toml or .json), used
+    /// instead of --divide and --properties
+    #[arg(long, conflicts_with_all = ["divide", "properties"])]
+    problem: Option<PathBuf>,
+
+    #[arg(long, default_value = "./property/divide10.txt")]
+    divide: PathBuf,
+
+    #[arg(long, default_value = "./property/properties100.txt")]
+    properties: PathBuf,
+
+    /// Expected number of heirs (taken from divide<N>.txt otherwise)
+    #[arg(long)]
+    heirs: Option<usize>,
+
+    /// Scale the shares so that they sum to 1
+    #[arg(long)]
+    normalize: bool,
+
+    /// Exit instead of fitting when the dataset has warnings
+    #[arg(long)]
+    strict: bool,
+
+    /// [default: 5000, or the generations of the resumed checkpoint]
+    #[arg(long)]
+    generations: Option<usize>,
+
+    /// Continue the run saved in a checkpoint file. Dataset and parameter
+    /// options are then ignored.
+    #[arg(long)]
+    resume: Option<PathBuf>,
+
+    /// Save checkpoints to this file, when the run ends and every
+    /// --checkpoint-interval generations
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    #[arg(long, default_value_t = 100)]
+    checkpoint_interval: usize,
+
+    /// Stop once the best fitness is at or below this value
+    #[arg(long)]
+    target_fitness: Option<f64>,
+
+    /// Stop after this many generations without a better gene
+    #[arg(long)]
+    stagnation_limit: Option<usize>,
+
+    /// Stop after this many seconds of fitting
+    #[arg(long)]
+    time_limit: Option<f64>,
+
+    /// Stop after this many fitness evaluations
+    #[arg(long)]
+    max_evaluations: Option<u64>,
+
+    /// Stop once the diversity of a generation is at or below this value
+    #[arg(long)]
+    min_diversity: Option<f64>,
+
+    /// Export the results (.json or .csv)
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Render the fitness chart (.png or .svg)
+    #[cfg(feature = "plot")]
+    #[arg(long)]
+    plot: Option<PathBuf>,
+
+    #[cfg(feature = "plot")]
+    #[arg(long, default_value_t = 1920)]
+    plot_width: u32,
+
+    #[cfg(feature = "plot")]
+    #[arg(long, default_value_t = 1080)]
+    plot_height: u32,
+
+    #[cfg(feature = "plot")]
+    #[arg(long)]
+    plot_title: Option<String>,
+
+    /// Series to draw: best, average, diff
+    #[cfg(feature = "plot")]
+    #[arg(long, value_delimiter = ',', default_value = "best,average,diff")]
+    plot_series: Vec<PlotSeries>,
+
+    /// Font file for the chart text (.ttf)
+    #[cfg(feature = "plot")]
+    #[arg(long)]
+    plot_font: Option<PathBuf>,
+
+    /// Worker threads (all cores otherwise)
+    #[cfg(feature = "parallel")]
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Random seed (a random one is drawn and printed otherwise)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    #[arg(long, default_value_t = 500)]
+    gene_len: usize,
+
+    #[arg(long, default_value_t = 0.2)]
+    mutation_probability: f64,
+
+    #[arg(long, default_value_t = 5)]
+    mutation_gene_data_len: usize,
+
+    #[arg(long, default_value_t = MutationKind::RandomReset)]
+    mutation: MutationKind,
+
+    #[arg(long, default_value_t = 0.01)]
+    mutation_rate: f64,
 
-fn main() {
-    // Get console arguments
-    let args: Vec<String> = env::args().collect();
+    #[arg(long, default_value_t = MutationSchedule::Constant)]
+    mutation_schedule: MutationSchedule,
 
-    if args.contains(&String::from("--console")) {
+    #[arg(long, default_value_t = 0.999)]
+    mutation_decay: f64,
+
+    #[arg(long, default_value_t = 100)]
+    stagnation_generations: usize,
+
+    #[arg(long, default_value_t = 2.0)]
+    stagnation_boost: f64,
+
+    #[arg(long, default_value_t = 0.1)]
+    elite_conservation_probability: f64,
+
+    #[arg(long, default_value_t = 0.9)]
+    total_conservation_probability: f64,
+
+    #[arg(long, default_value_t = SelectionKind::Truncation)]
+    selection: SelectionKind,
+
+    #[arg(long, default_value_t = 3)]
+    tournament_size: usize,
+
+    #[arg(long, default_value_t = 1.5)]
+    rank_pressure: f64,
+
+    #[arg(long, default_value_t = CrossoverKind::Uniform)]
+    crossover: CrossoverKind,
+
+    #[arg(long, default_value_t = 1.0)]
+    crossover_probability: f64,
+
+    #[arg(long, default_value_t = 3)]
+    crossover_points: usize,
+
+    #[arg(long, default_value_t = 0.5)]
+    uniform_bias: f64,
+
+    #[arg(long, default_value_t = DiversityMetric::Fitness)]
+    diversity_metric: DiversityMetric,
+
+    /// Gene pairs compared per generation by the genotype diversity
+    #[arg(long, default_value_t = 1000)]
+    diversity_samples: usize,
+
+    /// single or nsga2 (Pareto front of value deviation, item count
+    /// deviation and largest share error)
+    #[arg(long, default_value_t = ObjectiveMode::Single)]
+    objectives: ObjectiveMode,
+
+    /// l1, l2, linf (largest heir error), relative (error per share) or
+    /// currency (misallocated value)
+    #[arg(long, default_value_t = FitnessMetric::L1)]
+    metric: FitnessMetric,
+
+    /// repair or penalty, for the constraints of the problem file
+    /// (default: the one of the file, else repair)
+    #[arg(long)]
+    constraint_handling: Option<ConstraintHandling>,
+
+    /// Added to the fitness per violated constraint, as a fraction of the
+    /// worst fitness of the metric
+    #[arg(long)]
+    constraint_penalty: Option<f64>,
+
+    /// Sub-populations evolved in their own threads (1: a single population)
+    #[arg(long, default_value_t = 1)]
+    islands: usize,
+
+    /// Generations between migrations
+    #[arg(long, default_value_t = 50)]
+    migration_interval: usize,
+
+    /// Best genes sent by each island per migration
+    #[arg(long, default_value_t = 2)]
+    migrant_count: usize,
+
+    #[arg(long, default_value_t = Topology::Ring)]
+    topology: Topology,
+
+    #[arg(long, default_value_t = Replacement::Worst)]
+    replacement: Replacement,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        // BATCH MODE
+        Some(Command::Run(args)) => run_batch(&args),
         // CLI MODE
-        run_cli();
-    } else {
+        None if cli.console => match &cli.resume {
+            Some(resume) => resume_cli(resume, cli.checkpoint.clone()),
+            None => run_cli(cli.problem.as_deref(), cli.checkpoint.clone(), &cli.operators),
+        },
         // GUI MODE
-        run_gui();
+        None => {
+            run_gui();
+            ExitCode::SUCCESS
+        }
     }
 }
 
@@ -28,61 +311,456 @@ fn run_gui() {
     app.run_native();
 }
 
-fn run_cli() {
-        // Welcome
+fn run_cli(problem_file_path: Option<&Path>, checkpoint_path: Option<PathBuf>, operators: &OperatorArgs) -> ExitCode {
+    // Welcome
     println!("\nGenetic Algorithm Example with Rust");
     println!(" -- Property distribution problem");
     println!(" -- YeJun, Jung (yejun614@naver.com)");
     println!(" -- 2022-08-31");
-    print!("\n");
+    println!();
 
-    // Read datasets
-    let mut divide_file_path = String::new();
-    let mut properties_file_path = String::new();
+    // Read datasets (--problem, or a number of the bundled files, or a path)
+    let (loaded, expected_share_count) = match problem_file_path {
+        Some(problem_file_path) => (load_problem(Some(problem_file_path), Path::new(""), Path::new("")), None),
+        None => {
+            let mut divide_file_path = String::new();
+            let mut properties_file_path = String::new();
 
-    read_line_with_default("Divide File Path", &mut divide_file_path, "10".to_string());
-    divide_file_path = format!("./property/divide{}.txt", divide_file_path);
+            read_line_with_default("Divide File Path", &mut divide_file_path, "10".to_string());
+            let divide_file_path = bundled_path("divide", &divide_file_path);
 
-    read_line_with_default("Properties File Path", &mut properties_file_path, "100".to_string());
-    properties_file_path = format!("./property/properties{}.txt", properties_file_path);
+            read_line_with_default("Properties File Path", &mut properties_file_path, "100".to_string());
+            let properties_file_path = bundled_path("properties", &properties_file_path);
 
-    let (divide, properties) = load_dataset(&divide_file_path, &properties_file_path);
+            (load_problem(None, &divide_file_path, &properties_file_path), dataset::expected_share_count(&divide_file_path))
+        }
+    };
+
+    let mut problem = match loaded {
+        Ok(problem) => problem,
+        Err(err) => {
+            eprintln!("[ERROR] {}", err);
+            return ExitCode::from(EXIT_DATASET_ERROR);
+        }
+    };
     println!(" [Done] Dataset is loaded.\n");
 
-    // Create genetic algorithm model
-    let mut model = GAModel::default();
+    // Validate datasets
+    let report = validate_dataset(&problem.divide, &problem.properties, expected_share_count);
+    print_dataset_report(&report);
+
+    if !report.shares_sum_to_one() {
+        let mut normalize = false;
+        read_line_with_default("Normalize shares", &mut normalize, false);
 
-    // Set datasets
-    model.divide = divide;
-    model.properties = properties;
+        if normalize {
+            normalize_shares(&mut problem.divide);
+            println!(" [Done] Shares are normalized.\n");
+        }
+    }
+
+    read_line_with_default("problem.metric", &mut problem.metric, FitnessMetric::L1);
+
+    if !problem.constraints.is_empty() {
+        let (handling, penalty) = (problem.constraints.handling, problem.constraints.penalty);
+        read_line_with_default("problem.constraints.handling", &mut problem.constraints.handling, handling);
+        read_line_with_default("problem.constraints.penalty", &mut problem.constraints.penalty, penalty);
+    }
+
+    // Create genetic algorithm model
+    let mut model = GAModel::new(problem);
 
     // Input model properties
-    read_line_with_default("model.gene_len", &mut model.gene_len, 500 as usize);
+    read_line_with_default("model.gene_len", &mut model.gene_len, 500_usize);
     read_line_with_default("model.mutation_probability", &mut model.mutation_probability, 0.2);
-    read_line_with_default("model.mutation_gene_data_len", &mut model.mutation_gene_data_len, 5 as usize);
+    read_line_with_default("model.mutation_gene_data_len", &mut model.mutation_gene_data_len, 5_usize);
     read_line_with_default("model.elite_conservation_probability", &mut model.elite_conservation_probability, 0.1);
     read_line_with_default("model.total_conservation_probability", &mut model.total_conservation_probability, 0.9);
 
-    let mut generation = 0 as usize;
-    read_line_with_default("generation", &mut generation, 5000 as usize);
+    // Selection strategy (--selection truncation|tournament|roulette|rank)
+    match operators.selection {
+        Some(value) => {
+            model.selection_kind = value;
+        }
+        None => {
+            read_line_with_default("model.selection_kind", &mut model.selection_kind, SelectionKind::Truncation);
+        }
+    }
+
+    match model.selection_kind {
+        SelectionKind::Tournament => read_line_with_default("model.tournament_size", &mut model.tournament_size, 3_usize),
+        SelectionKind::Rank => read_line_with_default("model.rank_pressure", &mut model.rank_pressure, 1.5),
+        _ => {}
+    }
+
+    // Crossover operator (--crossover one-point|two-point|k-point|uniform)
+    match operators.crossover {
+        Some(value) => {
+            model.crossover_kind = value;
+        }
+        None => {
+            read_line_with_default("model.crossover_kind", &mut model.crossover_kind, CrossoverKind::Uniform);
+        }
+    }
+
+    match model.crossover_kind {
+        CrossoverKind::KPoint => read_line_with_default("model.crossover_points", &mut model.crossover_points, 3_usize),
+        CrossoverKind::Uniform => read_line_with_default("model.uniform_bias", &mut model.uniform_bias, 0.5),
+        _ => {}
+    }
+
+    read_line_with_default("model.crossover_probability", &mut model.crossover_probability, 1.0);
+
+    // Mutation operator (--mutation random-reset|bernoulli|swap|move-to-under-allocated)
+    match operators.mutation {
+        Some(value) => {
+            model.mutation_kind = value;
+        }
+        None => {
+            read_line_with_default("model.mutation_kind", &mut model.mutation_kind, MutationKind::RandomReset);
+        }
+    }
+
+    if model.mutation_kind == MutationKind::Bernoulli {
+        read_line_with_default("model.mutation_rate", &mut model.mutation_rate, 0.01);
+    }
+
+    // Mutation schedule (--mutation-schedule constant|decay|adaptive)
+    match operators.mutation_schedule {
+        Some(value) => {
+            model.mutation_schedule = value;
+        }
+        None => {
+            read_line_with_default("model.mutation_schedule", &mut model.mutation_schedule, MutationSchedule::Constant);
+        }
+    }
+
+    match model.mutation_schedule {
+        MutationSchedule::Decay => read_line_with_default("model.mutation_decay", &mut model.mutation_decay, 0.999),
+        MutationSchedule::Adaptive => {
+            read_line_with_default("model.stagnation_generations", &mut model.stagnation_generations, 100_usize);
+            read_line_with_default("model.stagnation_boost", &mut model.stagnation_boost, 2.0);
+        }
+        _ => {}
+    }
+
+    read_line_with_default("model.diversity_metric", &mut model.diversity_metric, DiversityMetric::Fitness);
+
+    if model.diversity_metric == DiversityMetric::Genotype {
+        read_line_with_default("model.diversity_samples", &mut model.diversity_samples, 1000_usize);
+    }
+
+    read_line_with_default("model.objective_mode", &mut model.objective_mode, ObjectiveMode::Single);
+
+    let default_seed = model.seed;
+    read_line_with_default("model.seed", &mut model.seed, default_seed);
+
+    let mut generation = 0_usize;
+    read_line_with_default("generation", &mut generation, DEFAULT_GENERATIONS);
+
+    if let Err(err) = model.validate() {
+        eprintln!("[ERROR] {}", err);
+        return ExitCode::from(EXIT_INVALID_PARAMETERS);
+    }
+
+    model.checkpoint_path = checkpoint_path;
+    model.observers.add(ConsoleObserver);
 
     // Start fitting
     let now = Instant::now();
     model.fit(generation);
     let elapsed = now.elapsed();
 
-    // Print fit results
+    print_results(&model.problem, &model.tracker, elapsed);
+
+    ExitCode::SUCCESS
+}
+
+fn resume_cli(resume: &Path, checkpoint_path: Option<PathBuf>) -> ExitCode {
+    let checkpoint = match Checkpoint::<PropertyDistribution>::load(resume) {
+        Ok(checkpoint) => checkpoint,
+        Err(err) => {
+            eprintln!("[ERROR] cannot load checkpoint {}: {}", resume.display(), err);
+            return ExitCode::from(EXIT_CHECKPOINT_ERROR);
+        }
+    };
+
+    println!(" [Resume] {} (generation {} of {})", resume.display(), checkpoint.tracker.total_generation, checkpoint.generations);
+
+    let mut generation = 0_usize;
+    read_line_with_default("generation", &mut generation, checkpoint.generations);
+
+    let mut model = GAModel::from_checkpoint(checkpoint);
+
+    if let Err(err) = model.validate() {
+        eprintln!("[ERROR] {}", err);
+        return ExitCode::from(EXIT_INVALID_PARAMETERS);
+    }
+
+    model.checkpoint_path = checkpoint_path;
+    model.observers.add(ConsoleObserver);
+
+    let now = Instant::now();
+    model.resume(generation);
+    let elapsed = now.elapsed();
+
+    print_results(&model.problem, &model.tracker, elapsed);
+
+    ExitCode::SUCCESS
+}
+
+fn run_batch(args: &RunArgs) -> ExitCode {
+    #[cfg(feature = "parallel")]
+    if let Some(threads) = args.threads {
+        if let Err(err) = rayon::ThreadPoolBuilder::new().num_threads(threads).build_global() {
+            eprintln!("[ERROR] {}", err);
+            return ExitCode::from(EXIT_INVALID_PARAMETERS);
+        }
+    }
+
+    let (mut model, generations) = match &args.resume {
+        Some(resume) => match Checkpoint::<PropertyDistribution>::load(resume) {
+            Ok(checkpoint) => {
+                let generations = args.generations.unwrap_or(checkpoint.generations);
+                (GAModel::from_checkpoint(checkpoint), generations)
+            }
+            Err(err) => {
+                eprintln!("[ERROR] cannot load checkpoint {}: {}", resume.display(), err);
+                return ExitCode::from(EXIT_CHECKPOINT_ERROR);
+            }
+        },
+        None => match new_model(args) {
+            Ok(model) => (model, args.generations.unwrap_or(DEFAULT_GENERATIONS)),
+            Err(code) => return code,
+        },
+    };
+
+    model.checkpoint_path = args.checkpoint.clone();
+    model.checkpoint_interval = args.checkpoint_interval;
+
+    let now = Instant::now();
+
+    let tracker = if args.islands > 1 {
+        if args.resume.is_some() || args.checkpoint.is_some() {
+            eprintln!("[ERROR] checkpoints are not supported with --islands");
+            return ExitCode::from(EXIT_INVALID_PARAMETERS);
+        }
+
+        let mut island_model = IslandModel::new(model.clone(), IslandParameters {
+            island_count: args.islands,
+            migration_interval: args.migration_interval,
+            migrant_count: args.migrant_count,
+            topology: args.topology,
+            replacement: args.replacement,
+        });
+
+        if let Err(err) = island_model.validate() {
+            eprintln!("[ERROR] {}", err);
+            return ExitCode::from(EXIT_INVALID_PARAMETERS);
+        }
+
+        island_model.observers.add(ConsoleObserver);
+
+        island_model.run(generations);
+        island_model.tracker
+    } else {
+        if let Err(err) = model.validate() {
+            eprintln!("[ERROR] {}", err);
+            return ExitCode::from(EXIT_INVALID_PARAMETERS);
+        }
+
+        model.observers.add(ConsoleObserver);
+
+        match args.resume {
+            Some(_) => model.resume(generations),
+            None => model.run(generations),
+        }
+        model.tracker
+    };
+
+    let elapsed = now.elapsed();
+
+    print_results(&model.problem, &tracker, elapsed);
+
+    if let Some(output) = &args.output {
+        match export(&tracker, output) {
+            Ok(()) => println!(" [Export] {}", output.display()),
+            Err(err) => {
+                eprintln!("[ERROR] export failed: {}", err);
+                return ExitCode::from(EXIT_EXPORT_ERROR);
+            }
+        }
+    }
+
+    #[cfg(feature = "plot")]
+    if let Some(plot) = &args.plot {
+        let options = PlotOptions {
+            width: args.plot_width,
+            height: args.plot_height,
+            title: args.plot_title.clone(),
+            series: args.plot_series.clone(),
+            font: args.plot_font.clone(),
+        };
+
+        match tracker.get_graph(plot, &options) {
+            Ok(()) => println!(" [Plot] {}", plot.display()),
+            Err(err) => {
+                eprintln!("[ERROR] plot failed: {}", err);
+                return ExitCode::from(EXIT_EXPORT_ERROR);
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+// Loads the dataset and applies the parameter options
+fn new_model(args: &RunArgs) -> Result<GAModel, ExitCode> {
+    let mut problem = match load_problem(args.problem.as_deref(), &args.divide, &args.properties) {
+        Ok(problem) => problem,
+        Err(err) => {
+            eprintln!("[ERROR] {}", err);
+            return Err(ExitCode::from(EXIT_DATASET_ERROR));
+        }
+    };
+
+    let expected_share_count = match args.problem {
+        Some(_) => args.heirs,
+        None => args.heirs.or(dataset::expected_share_count(&args.divide)),
+    };
+
+    if args.normalize {
+        normalize_shares(&mut problem.divide);
+    }
+
+    problem.metric = args.metric;
+
+    if let Some(handling) = args.constraint_handling {
+        problem.constraints.handling = handling;
+    }
+
+    if let Some(penalty) = args.constraint_penalty {
+        problem.constraints.penalty = penalty;
+    }
+
+    let report = validate_dataset(&problem.divide, &problem.properties, expected_share_count);
+    print_dataset_report(&report);
+
+    if args.strict && !report.is_valid() {
+        eprintln!("[ERROR] dataset has warnings (--strict)");
+        return Err(ExitCode::from(EXIT_DATASET_WARNING));
+    }
+
+    let mut model = GAModel::new(problem);
+
+    model.gene_len = args.gene_len;
+    model.mutation_probability = args.mutation_probability;
+    model.mutation_gene_data_len = args.mutation_gene_data_len;
+    model.mutation_kind = args.mutation;
+    model.mutation_rate = args.mutation_rate;
+    model.mutation_schedule = args.mutation_schedule;
+    model.mutation_decay = args.mutation_decay;
+    model.stagnation_generations = args.stagnation_generations;
+    model.stagnation_boost = args.stagnation_boost;
+    model.elite_conservation_probability = args.elite_conservation_probability;
+    model.total_conservation_probability = args.total_conservation_probability;
+    model.selection_kind = args.selection;
+    model.tournament_size = args.tournament_size;
+    model.rank_pressure = args.rank_pressure;
+    model.crossover_kind = args.crossover;
+    model.crossover_probability = args.crossover_probability;
+    model.crossover_points = args.crossover_points;
+    model.uniform_bias = args.uniform_bias;
+    model.diversity_metric = args.diversity_metric;
+    model.diversity_samples = args.diversity_samples;
+    model.objective_mode = args.objectives;
+    model.termination = Termination {
+        target_fitness: args.target_fitness,
+        stagnation_limit: args.stagnation_limit,
+        time_limit_seconds: args.time_limit,
+        max_evaluations: args.max_evaluations,
+        min_diversity: args.min_diversity,
+    };
+
+    if let Some(seed) = args.seed {
+        model.seed = seed;
+    }
+
+    Ok(model)
+}
+
+// A problem file, or the divide and properties files joined by position
+fn load_problem(problem_file_path: Option<&Path>, divide_file_path: &Path, properties_file_path: &Path) -> Result<PropertyDistribution, DatasetError> {
+    match problem_file_path {
+        Some(problem_file_path) => Ok(load_problem_file(&problem_file_path.to_string_lossy())?.problem()),
+        None => {
+            let (divide, properties) = load_dataset(&divide_file_path.to_string_lossy(), &properties_file_path.to_string_lossy())?;
+            Ok(PropertyDistribution::new(divide, properties))
+        }
+    }
+}
+
+// "10" -> ./property/divide10.txt, anything else is taken as a path
+fn bundled_path(name: &str, value: &str) -> PathBuf {
+    match value.parse::<usize>() {
+        Ok(number) => PathBuf::from(format!("./property/{}{}.txt", name, number)),
+        Err(_err) => PathBuf::from(value),
+    }
+}
+
+fn print_dataset_report(report: &DatasetReport) {
+    println!(" [Dataset] {} shares (sum {}), {} zero-value properties", report.share_count, report.share_sum, report.zero_properties.len());
+
+    for warning in report.warnings() {
+        eprintln!("[WARNING] {}", warning);
+    }
+}
+
+fn print_results(problem: &PropertyDistribution, tracker: &GAModelTracker, elapsed: Duration) {
     println!("[Fit results]");
     println!(" [Elapsed] {:?}", elapsed);
+    println!(" [Seed] {}", tracker.seed);
+    println!(" [Generations] {}", tracker.total_generation);
+    println!(" [Evaluations] {}", tracker.evaluations);
+
+    if let Some(stop_reason) = tracker.stop_reason {
+        println!(" [Stop reason] {}", stop_reason);
+    }
 
     println!(" [Gene data]");
-    println!("{:?}\n", model.tracker.best_gene.data);
+    println!("{:?}\n", tracker.best_gene.data);
 
-    println!(" [Best fitness] {}", model.tracker.best_gene.fitness);
+    // Only problem files name their items
+    if !problem.item_names.is_empty() {
+        println!(" [Assignment]");
+        for (item, heir) in problem.assignment(&tracker.best_gene.data) {
+            println!("  {} → {}", item, heir);
+        }
+        println!();
+    }
 
-    let properties_sum: i32 = model.properties.iter().sum();
-    println!(" [Real fitness] {}",  (properties_sum as f64) * model.tracker.best_gene.fitness);
+    for (island, changes) in tracker.island_best_fitness_changes.iter().enumerate() {
+        println!(" [Island {}] best fitness: {}", island, changes.iter().copied().fold(f64::MAX, f64::min));
+    }
 
-    // Export fitness plots
-    // model.tracker.get_graph("fitness_chagnes.png");
+    if !tracker.pareto_front.is_empty() {
+        println!(" [Pareto front] {}", tracker.objective_names.join(", "));
+        for gene in &tracker.pareto_front {
+            let objectives: Vec<String> = gene.objectives.iter().map(|objective| objective.to_string()).collect();
+            println!("  {}", objectives.join(", "));
+        }
+        println!();
+    }
+
+    println!(" [Best fitness] {} ({})", tracker.best_gene.fitness, problem.metric);
+    println!(" [{}] {}", problem.metric.real_name(), problem.real_fitness(&tracker.best_gene.data));
+
+    if !problem.constraints.is_empty() {
+        let violations = problem.violations(&tracker.best_gene.data);
+
+        println!(" [Constraints] {}, {} violated", problem.constraints.handling, violations.len());
+        for violation in violations {
+            println!("  {}", violation);
+        }
+    }
 }
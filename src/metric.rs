@@ -0,0 +1,153 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use serde::{Serialize, Deserialize};
+
+// How far an allocation is from the target shares. The error of a heir is
+// its achieved share minus its target share.
+//  - L1: sum of the heir errors
+//  - L2: root of the sum of the squared heir errors
+//  - LInf: largest heir error
+//  - Relative: average heir error divided by the target share
+//  - Currency: value given to the wrong heirs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FitnessMetric {
+    #[default]
+    L1,
+    L2,
+    #[serde(rename = "linf")]
+    LInf,
+    Relative,
+    Currency,
+}
+
+named_enum!(FitnessMetric, "fitness metric", {
+    L1 => "l1",
+    L2 => "l2",
+    LInf => "linf",
+    Relative => "relative",
+    Currency => "currency",
+});
+
+impl FitnessMetric {
+    // `values`: value given to each heir
+    pub fn fitness(&self, divide: &[f64], values: &[i32], properties_sum: i32) -> f64 {
+        let total = properties_sum as f64;
+        let errors = divide.iter()
+            .zip(values.iter())
+            .map(|(share, value)| ((*value as f64) / total - share).abs());
+
+        match self {
+            FitnessMetric::L1 => errors.sum(),
+            FitnessMetric::L2 => errors.map(|error| error * error).sum::<f64>().sqrt(),
+            FitnessMetric::LInf => errors.fold(0.0, f64::max),
+            FitnessMetric::Relative => {
+                // A heir without a share counts its plain error
+                let sum: f64 = errors.zip(divide.iter())
+                    .map(|(error, share)| if *share > 0.0 { error / share } else { error })
+                    .sum();
+
+                sum / (divide.len().max(1) as f64)
+            }
+            // Every misallocated unit is missing from one heir and given to another
+            FitnessMetric::Currency => errors.sum::<f64>() * total / 2.0,
+        }
+    }
+
+    // Largest fitness of any allocation. Every metric is convex in the
+    // values, so it is reached by giving everything to a single heir.
+    pub fn worst(&self, divide: &[f64], properties_sum: i32) -> f64 {
+        let mut values = vec![0; divide.len()];
+
+        (0..divide.len())
+            .map(|heir| {
+                values.fill(0);
+                values[heir] = properties_sum;
+                self.fitness(divide, &values, properties_sum)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    // Fitness in the unit of `real_name`
+    pub fn real_value(&self, fitness: f64, properties_sum: i32) -> f64 {
+        let total = properties_sum as f64;
+
+        match self {
+            FitnessMetric::L1 => fitness * total / 2.0,
+            FitnessMetric::L2 | FitnessMetric::LInf => fitness * total,
+            FitnessMetric::Relative => fitness * 100.0,
+            FitnessMetric::Currency => fitness,
+        }
+    }
+
+    pub fn real_name(&self) -> &'static str {
+        match self {
+            FitnessMetric::L1 | FitnessMetric::Currency => "Misallocated value",
+            FitnessMetric::L2 => "Root squared value error",
+            FitnessMetric::LInf => "Largest value error of a heir",
+            FitnessMetric::Relative => "Average relative error (%)",
+        }
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            FitnessMetric::L1 => "Sum of |achieved share - target share| over the heirs. Every misallocated unit is counted twice (missing from one heir, extra for another), so the real value is half of it times the total value.",
+            FitnessMetric::L2 => "Root of the summed squared share errors. Large errors of a single heir weigh more than many small ones. The real value is in the unit of the property values.",
+            FitnessMetric::LInf => "Share error of the worst-off heir only. The real value is how much that heir is off, in the unit of the property values.",
+            FitnessMetric::Relative => "Share error of every heir divided by its target share, averaged. Small shares matter as much as large ones. The real value is in percent of the share.",
+            FitnessMetric::Currency => "Value given to the wrong heirs, in the unit of the property values. The fitness already is the real value.",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shares 0.6, 0.2 and 0.2 of a total of 100 for targets 0.5, 0.25 and
+    // 0.25: heir errors 0.1, 0.05 and 0.05
+    const DIVIDE: [f64; 3] = [0.5, 0.25, 0.25];
+    const VALUES: [i32; 3] = [60, 20, 20];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-12, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn fitness_and_real_value_of_a_known_allocation() {
+        let cases = [
+            (FitnessMetric::L1, 0.2, 10.0),
+            (FitnessMetric::L2, 0.015_f64.sqrt(), 0.015_f64.sqrt() * 100.0),
+            (FitnessMetric::LInf, 0.1, 10.0),
+            (FitnessMetric::Relative, 0.2, 20.0),
+            (FitnessMetric::Currency, 10.0, 10.0),
+        ];
+
+        for (metric, fitness, real_value) in cases {
+            assert_close(metric.fitness(&DIVIDE, &VALUES, 100), fitness);
+            assert_close(metric.real_value(fitness, 100), real_value);
+        }
+
+        // A heir without a share counts its plain error
+        assert_close(FitnessMetric::Relative.fitness(&[1.0, 0.0], &[50, 50], 100), 0.5);
+    }
+
+    #[test]
+    fn worst_gives_everything_to_the_heir_of_a_small_share() {
+        let cases = [
+            (FitnessMetric::L1, 1.5),
+            (FitnessMetric::L2, 0.875_f64.sqrt()),
+            (FitnessMetric::LInf, 0.75),
+            (FitnessMetric::Relative, 5.0 / 3.0),
+            (FitnessMetric::Currency, 75.0),
+        ];
+
+        for (metric, worst) in cases {
+            assert_close(metric.worst(&DIVIDE, 100), worst);
+            assert!(metric.fitness(&DIVIDE, &VALUES, 100) <= worst);
+        }
+    }
+}
@@ -5,21 +5,63 @@
 
 use std::thread;
 use std::sync::mpsc;
+use std::path::PathBuf;
 use std::time::{Instant, Duration};
 use std::io::{stdout, stdin, Write, Read};
-use rand::prelude::{Rng};
-// use plotters::prelude::*;
+use rand::prelude::{Rng, SeedableRng};
+use rand::seq::index::sample;
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use chrono::{Local, DateTime};
-
-#[derive(Clone)]
+use serde::{Serialize, Deserialize};
+
+use super::problem::{Problem, PropertyDistribution};
+use super::crossover::{CrossoverKind, CrossoverOperator, OnePoint, TwoPoint, KPoint, Uniform};
+use super::mutation::{MutationKind, MutationSchedule, MutationOperator, RandomReset, Bernoulli, Swap, MoveToUnderAllocated};
+use super::selection::{SelectionKind, SelectionStrategy, Truncation, Tournament, Weighted};
+use super::diversity::{DiversityMetric, fitness_diversity, genotype_diversity};
+use super::island::IslandParameters;
+use super::checkpoint::Checkpoint;
+use super::termination::{Termination, StopReason};
+use super::control::{self, Command, Controlled, ParameterChange};
+use super::observer::{Observer, Observers};
+use super::log::{LogEntry, LogLevel};
+use super::nsga::{ObjectiveMode, crowded_sort, pareto_front};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GAModelTracker {
     pub local_datetime: DateTime<Local>,
     pub is_running: bool,
+    pub is_paused: bool,
+    pub seed: u64,
+    pub parameters: Option<GAParameters>,
+    pub islands: Option<IslandParameters>,
+    #[serde(
+        rename = "elapsed_seconds",
+        serialize_with = "super::export::serialize_seconds",
+        deserialize_with = "super::export::deserialize_seconds"
+    )]
+    pub elapsed: Duration,
     pub best_gene: Gene,
+    // Non-dominated genes of the last generation, empty unless the objective
+    // mode is `ObjectiveMode::Nsga2`
+    pub objective_names: Vec<String>,
+    pub pareto_front: Vec<Gene>,
+    // Bumped whenever `pareto_front` changes
+    #[serde(skip)]
+    pub pareto_front_version: u64,
+    pub best_generation: i32,
     pub total_generation: i32,
+    pub evaluations: u64,
+    pub stop_reason: Option<StopReason>,
+    // Parameters changed by `Command::SetParameters` during the run
+    pub parameter_changes: Vec<ParameterChange>,
     pub best_fitness_changes: Vec<f64>,
     pub average_fitness_changes: Vec<f64>,
     pub average_diff_changes: Vec<f64>,
+    // Best fitness of each island per generation, empty for a single population
+    pub island_best_fitness_changes: Vec<Vec<f64>>,
 }
 
 impl Default for GAModelTracker {
@@ -27,284 +69,857 @@ impl Default for GAModelTracker {
         Self {
             local_datetime: Local::now(),
             is_running: false,
-            best_gene: Gene { data: Vec::new(), fitness: 1.0 },
+            is_paused: false,
+            seed: 0,
+            parameters: None,
+            islands: None,
+            elapsed: Duration::ZERO,
+            best_gene: Gene::default(),
+            objective_names: Vec::new(),
+            pareto_front: Vec::new(),
+            pareto_front_version: 0,
+            best_generation: 0,
             total_generation: 0,
+            evaluations: 0,
+            stop_reason: None,
+            parameter_changes: Vec::new(),
             best_fitness_changes: Vec::<f64>::new(),
             average_fitness_changes: Vec::<f64>::new(),
             average_diff_changes: Vec::<f64>::new(),
+            island_best_fitness_changes: Vec::new(),
         }
     }
 }
 
 impl GAModelTracker {
-    fn reset(&mut self) {
+    pub(crate) fn reset(&mut self) {
+        self.local_datetime = Local::now();
+        self.elapsed = Duration::ZERO;
         self.total_generation = 0;
-        self.best_gene = Gene { data: Vec::new(), fitness: 1.0 };
+        self.evaluations = 0;
+        self.stop_reason = None;
+        self.parameter_changes.clear();
+        self.best_gene = Gene::default();
+        self.objective_names.clear();
+        self.set_pareto_front(Vec::new());
+        self.best_generation = 0;
         self.best_fitness_changes.clear();
         self.average_fitness_changes.clear();
         self.average_diff_changes.clear();
+        self.island_best_fitness_changes.clear();
+    }
+
+    // Finished generations since the one that found the best gene (0 right
+    // after an improvement)
+    pub fn stagnation(&self) -> usize {
+        (self.total_generation - 1 - self.best_generation).max(0) as usize
+    }
+
+    pub(crate) fn set_pareto_front(&mut self, front: Vec<Gene>) {
+        let is_same = front.len() == self.pareto_front.len()
+            && front.iter().zip(self.pareto_front.iter()).all(|(a, b)| a.data == b.data && a.objectives == b.objectives);
+
+        if !is_same {
+            self.pareto_front = front;
+            self.pareto_front_version += 1;
+        }
     }
 
-    fn append(&mut self, best_fitness: f64, average_fitness: f64, average_diff: f64) {
+    pub(crate) fn append(&mut self, best_fitness: f64, average_fitness: f64, average_diff: f64) {
         self.total_generation += 1;
         self.best_fitness_changes.push(best_fitness);
         self.average_fitness_changes.push(average_fitness);
         self.average_diff_changes.push(average_diff);
     }
 
-    // pub fn get_graph(&self, file_name: &str) {
-    //     let max_y = self.average_fitness_changes[0];
-    //     let plt = BitMapBackend::new(file_name, (1920, 1080)).into_drawing_area();
-    //     plt.fill(&WHITE).unwrap();
+    // Everything but the history before `from_generation` and the parameter
+    // changes before `from_parameter_change`. The best gene and the Pareto
+    // front are left out unless `with_best_gene` and `with_pareto_front` are set.
+    pub fn update_since(&self, from_generation: usize, from_parameter_change: usize, with_best_gene: bool, with_pareto_front: bool) -> TrackerUpdate {
+        let from_generation = from_generation.min(self.best_fitness_changes.len());
+        let from_parameter_change = from_parameter_change.min(self.parameter_changes.len());
+
+        TrackerUpdate {
+            head: GAModelTracker {
+                local_datetime: self.local_datetime,
+                is_running: self.is_running,
+                is_paused: self.is_paused,
+                seed: self.seed,
+                parameters: self.parameters.clone(),
+                islands: self.islands.clone(),
+                elapsed: self.elapsed,
+                best_gene: Gene::default(),
+                objective_names: self.objective_names.clone(),
+                pareto_front: Vec::new(),
+                pareto_front_version: self.pareto_front_version,
+                best_generation: self.best_generation,
+                total_generation: self.total_generation,
+                evaluations: self.evaluations,
+                stop_reason: self.stop_reason,
+                parameter_changes: Vec::new(),
+                best_fitness_changes: Vec::new(),
+                average_fitness_changes: Vec::new(),
+                average_diff_changes: Vec::new(),
+                island_best_fitness_changes: Vec::new(),
+            },
+            from_generation,
+            best_gene: with_best_gene.then(|| self.best_gene.clone()),
+            pareto_front: with_pareto_front.then(|| self.pareto_front.clone()),
+            from_parameter_change,
+            parameter_changes: self.parameter_changes[from_parameter_change..].to_vec(),
+            best_fitness_changes: self.best_fitness_changes[from_generation..].to_vec(),
+            average_fitness_changes: self.average_fitness_changes[from_generation..].to_vec(),
+            average_diff_changes: self.average_diff_changes[from_generation..].to_vec(),
+            island_best_fitness_changes: self.island_best_fitness_changes.iter()
+                .map(|changes| changes[from_generation.min(changes.len())..].to_vec())
+                .collect(),
+        }
+    }
 
-    //     let mut chart = ChartBuilder::on(&plt)
-    //         .caption(format!("Model Fitness Changes (local_datetime: {:?})", self.local_datetime), ("Arial", 20))
-    //         .set_label_area_size(LabelAreaPosition::Left, 80)
-    //         .set_label_area_size(LabelAreaPosition::Right, 80)
-    //         .set_label_area_size(LabelAreaPosition::Top, 30)
-    //         .set_label_area_size(LabelAreaPosition::Bottom, 40)
-    //         .margin(10)
-    //         .build_cartesian_2d(-100..self.total_generation + 100, -1000..max_y + 1000)
-    //         .unwrap();
+    // Brings the tracker up to date with an update of the sending side
+    pub fn apply(&mut self, update: TrackerUpdate) {
+        let from_generation = update.from_generation;
+        let mut tracker = update.head;
+
+        tracker.best_gene = update.best_gene.unwrap_or_else(|| std::mem::take(&mut self.best_gene));
+        tracker.pareto_front = update.pareto_front.unwrap_or_else(|| std::mem::take(&mut self.pareto_front));
+        tracker.parameter_changes = extend_from(std::mem::take(&mut self.parameter_changes), update.from_parameter_change, update.parameter_changes);
+        tracker.best_fitness_changes = extend_from(std::mem::take(&mut self.best_fitness_changes), from_generation, update.best_fitness_changes);
+        tracker.average_fitness_changes = extend_from(std::mem::take(&mut self.average_fitness_changes), from_generation, update.average_fitness_changes);
+        tracker.average_diff_changes = extend_from(std::mem::take(&mut self.average_diff_changes), from_generation, update.average_diff_changes);
+        tracker.island_best_fitness_changes = update.island_best_fitness_changes.into_iter().enumerate().map(|(n, changes)| {
+            let island_changes = self.island_best_fitness_changes.get_mut(n).map(std::mem::take).unwrap_or_default();
+            extend_from(island_changes, from_generation, changes)
+        }).collect();
+
+        *self = tracker;
+    }
 
-    //     chart.configure_mesh().draw().unwrap();
+    #[cfg(feature = "plot")]
+    pub fn get_graph(&self, path: &std::path::Path, options: &super::plot::PlotOptions) -> Result<(), Box<dyn std::error::Error>> {
+        super::plot::plot(self, path, options)
+    }
+}
 
-    //     chart.draw_series(
-    //         LineSeries::new((0..self.total_generation).map(|x| (x, self.best_fitness_changes[x as usize])), &BLUE)
-    //     ).unwrap();
+// Changes of a tracker sent by a running fit, so that its whole history is
+// never copied again
+//  - head: the tracker without its history, best gene, Pareto front and
+//    parameter changes
+//  - best_gene, pareto_front: only when they changed since the last update
+//  - parameter_changes: from `from_parameter_change` on
+//  - *_changes: the history from `from_generation` on
+#[derive(Clone)]
+pub struct TrackerUpdate {
+    pub head: GAModelTracker,
+    pub from_generation: usize,
+    pub best_gene: Option<Gene>,
+    pub pareto_front: Option<Vec<Gene>>,
+    pub from_parameter_change: usize,
+    pub parameter_changes: Vec<ParameterChange>,
+    pub best_fitness_changes: Vec<f64>,
+    pub average_fitness_changes: Vec<f64>,
+    pub average_diff_changes: Vec<f64>,
+    pub island_best_fitness_changes: Vec<Vec<f64>>,
+}
 
-    //     chart.draw_series(
-    //         LineSeries::new((0..self.total_generation).map(|x| (x, self.average_fitness_changes[x as usize])), &RED)
-    //     ).unwrap();
+fn extend_from<T>(mut changes: Vec<T>, from: usize, new_changes: Vec<T>) -> Vec<T> {
+    changes.truncate(from);
+    changes.extend(new_changes);
+    changes
+}
 
-    //     chart.draw_series(
-    //         LineSeries::new((0..self.total_generation).map(|x| (x, self.average_diff_changes[x as usize])), &GREEN)
-    //     ).unwrap();
-    // }
+// Snapshot of every tunable field of `GAModel`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GAParameters {
+    pub gene_len: usize,
+    pub mutation_probability: f64,
+    pub mutation_gene_data_len: usize,
+    pub mutation_kind: MutationKind,
+    pub mutation_rate: f64,
+    pub mutation_schedule: MutationSchedule,
+    pub mutation_decay: f64,
+    pub stagnation_generations: usize,
+    pub stagnation_boost: f64,
+    pub elite_conservation_probability: f64,
+    pub total_conservation_probability: f64,
+    pub selection_kind: SelectionKind,
+    pub tournament_size: usize,
+    pub rank_pressure: f64,
+    pub crossover_kind: CrossoverKind,
+    pub crossover_probability: f64,
+    pub crossover_points: usize,
+    pub uniform_bias: f64,
+    pub diversity_metric: DiversityMetric,
+    pub diversity_samples: usize,
+    pub objective_mode: ObjectiveMode,
+    #[serde(flatten)]
+    pub termination: Termination,
+    pub seed: u64,
+}
+
+// Fields of `GAParameters` that can change between two generations of a run
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LiveParameters {
+    pub mutation_probability: f64,
+    pub mutation_gene_data_len: usize,
+    pub mutation_kind: MutationKind,
+    pub mutation_rate: f64,
+    pub mutation_schedule: MutationSchedule,
+    pub mutation_decay: f64,
+    pub stagnation_generations: usize,
+    pub stagnation_boost: f64,
+    pub elite_conservation_probability: f64,
+    pub total_conservation_probability: f64,
+    pub selection_kind: SelectionKind,
+    pub tournament_size: usize,
+    pub rank_pressure: f64,
 }
 
 #[derive(Clone)]
-pub struct GAModel {
+pub struct GAModel<P: Problem = PropertyDistribution> {
+    pub problem: P,
     pub genes: Vec<Gene>,
     pub gene_len: usize,
-    pub divide: Vec<f64>,
-    pub properties: Vec<i32>,
     pub mutation_probability: f64,
     pub mutation_gene_data_len: usize,
+    pub mutation_kind: MutationKind,
+    pub mutation_rate: f64,
+    pub mutation_schedule: MutationSchedule,
+    pub mutation_decay: f64,
+    pub stagnation_generations: usize,
+    pub stagnation_boost: f64,
     pub elite_conservation_probability: f64,
     pub total_conservation_probability: f64,
+    pub selection_kind: SelectionKind,
+    pub tournament_size: usize,
+    pub rank_pressure: f64,
+    pub crossover_kind: CrossoverKind,
+    pub crossover_probability: f64,
+    pub crossover_points: usize,
+    pub uniform_bias: f64,
+    pub diversity_metric: DiversityMetric,
+    pub diversity_samples: usize,
+    pub objective_mode: ObjectiveMode,
+    pub termination: Termination,
+    pub seed: u64,
     pub tracker: GAModelTracker,
+    // Saved every `checkpoint_interval` generations (0: only when the run
+    // ends or is stopped)
+    pub checkpoint_path: Option<PathBuf>,
+    pub checkpoint_interval: usize,
+    pub observers: Observers,
+    // Population the offspring compete with in `ObjectiveMode::Nsga2`
+    survivors: Vec<Gene>,
+    rng: ChaCha8Rng,
 }
 
-impl Default for GAModel {
+impl<P: Problem + Default> Default for GAModel<P> {
     fn default() -> Self {
+        Self::new(P::default())
+    }
+}
+
+impl<P: Problem> GAModel<P> {
+    pub fn new(problem: P) -> Self {
+        // Keep the default seed within u32 so it is shown exactly in the GUI
+        let seed = rand::random::<u32>() as u64;
+
         Self {
+            problem,
             genes: Vec::<Gene>::new(),
             gene_len: 500,
-            divide: Vec::<f64>::new(),
-            properties: Vec::<i32>::new(),
             mutation_probability: 0.2,
             mutation_gene_data_len: 5,
+            mutation_kind: MutationKind::RandomReset,
+            mutation_rate: 0.01,
+            mutation_schedule: MutationSchedule::Constant,
+            mutation_decay: 0.999,
+            stagnation_generations: 100,
+            stagnation_boost: 2.0,
             elite_conservation_probability: 0.1,
             total_conservation_probability: 0.9,
+            selection_kind: SelectionKind::Truncation,
+            tournament_size: 3,
+            rank_pressure: 1.5,
+            crossover_kind: CrossoverKind::Uniform,
+            crossover_probability: 1.0,
+            crossover_points: 3,
+            uniform_bias: 0.5,
+            diversity_metric: DiversityMetric::Fitness,
+            diversity_samples: 1000,
+            objective_mode: ObjectiveMode::Single,
+            termination: Termination::default(),
+            seed,
             tracker: GAModelTracker::default(),
+            checkpoint_path: None,
+            checkpoint_interval: 0,
+            observers: Observers::default(),
+            survivors: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(seed),
         }
     }
-}
 
-impl GAModel {
-    pub fn fit_back(&mut self, generations: usize) -> (thread::JoinHandle<()>, mpsc::Sender<bool>, mpsc::Receiver<GAModelTracker>) {
-        let (tx, rx) = mpsc::channel::<GAModelTracker>();
-        let (app_tx, app_rx) = mpsc::channel::<bool>();
-        let mut model = self.clone();
-        let mut now = Instant::now();
-
-        let handler = thread::spawn(move || {
-            model.tracker.reset();
-            model.tracker.is_running = true;
-            model.shake();
-
-            for generation in 0..generations {
-                match app_rx.try_recv() {
-                    Ok(is_running) => {
-                        if is_running {
-                            println!("STOPPED");
-                            break;
-                        }
-                    }
-                    Err(err) => {}
-                }
-
-                model.run_once(generation);
-
-                if now.elapsed() >= Duration::from_millis(50) {
-                    tx.send(model.tracker.clone()).unwrap();
-                    now = Instant::now();
-                }
-            }
-            println!("Done.");
+    pub fn fit_back(&mut self, generations: usize) -> (thread::JoinHandle<()>, mpsc::Sender<Command>, mpsc::Receiver<TrackerUpdate>) {
+        self.spawn_fit(generations, false)
+    }
 
-            model.tracker.is_running = false;
-            tx.send(model.tracker.clone()).unwrap();
-        });
+    // `fit_back` for a model restored by `from_checkpoint`
+    pub fn resume_back(&mut self, generations: usize) -> (thread::JoinHandle<()>, mpsc::Sender<Command>, mpsc::Receiver<TrackerUpdate>) {
+        self.spawn_fit(generations, true)
+    }
 
-        (handler, app_tx, rx)
+    fn spawn_fit(&mut self, generations: usize, resume: bool) -> (thread::JoinHandle<()>, mpsc::Sender<Command>, mpsc::Receiver<TrackerUpdate>) {
+        control::spawn(self, generations, resume)
     }
 
     pub fn fit(&mut self, generations: usize) {
         println!("\n ##################  PROPERTIES ##################\n");
 
         println!(" [local_datetime] {:?}", self.tracker.local_datetime);
-        print!("\n");
+        println!(" [seed] {}", self.seed);
+        println!();
 
         println!(" [gene_len] {}", self.gene_len);
         println!(" [mutataion_properbability] {}", self.mutation_probability);
         println!(" [mutation_gene_data_len] {}", self.mutation_gene_data_len);
+        println!(" [mutation_kind] {}", self.mutation_kind);
+        println!(" [mutation_rate] {}", self.mutation_rate);
+        println!(" [mutation_schedule] {}", self.mutation_schedule);
+        println!(" [mutation_decay] {}", self.mutation_decay);
+        println!(" [stagnation_generations] {}", self.stagnation_generations);
+        println!(" [stagnation_boost] {}", self.stagnation_boost);
         println!(" [elite_conservation_probability] {}", self.elite_conservation_probability);
         println!(" [total_conservation_probability] {}", self.total_conservation_probability);
-        print!("\n");
-
-        println!(" [divide] {:?}", self.divide);
-        println!(" [properties] {:?}", self.properties);
+        println!(" [selection_kind] {}", self.selection_kind);
+        println!(" [tournament_size] {}", self.tournament_size);
+        println!(" [rank_pressure] {}", self.rank_pressure);
+        println!(" [crossover_kind] {}", self.crossover_kind);
+        println!(" [crossover_probability] {}", self.crossover_probability);
+        println!(" [crossover_points] {}", self.crossover_points);
+        println!(" [uniform_bias] {}", self.uniform_bias);
+        println!(" [diversity_metric] {}", self.diversity_metric);
+        println!(" [diversity_samples] {}", self.diversity_samples);
+        println!(" [objective_mode] {}", self.objective_mode);
+        println!(" [termination] {:?}", self.termination);
+        println!();
+
+        println!(" [problem] {:?}", self.problem);
 
         println!("\n Press enter key to continue ...");
         stdout().flush().unwrap();
-        stdin().read(&mut [0]).unwrap();
+        // A closed stdin (end of file) starts the fit as well
+        let _ = stdin().read(&mut [0]);
 
         println!("\n ##################  FIT START  ##################\n");
 
-        self.tracker.reset();
-        self.shake();
+        self.run(generations);
 
-        for generation in 0..generations {
-            self.run_once(generation);
+        println!("\n ##################   FIT DONE  ##################\n");
+    }
+
+    // Fits without any prompt
+    pub fn run(&mut self, generations: usize) {
+        self.start();
+        self.resume(generations);
+    }
+
+    // Continues up to `generations` from the last finished generation
+    pub fn resume(&mut self, generations: usize) {
+        control::run(self, generations, None);
+    }
+
+    // Records the stopping criterion met by the last generation, if any
+    fn check_termination(&mut self) -> bool {
+        self.tracker.stop_reason = self.termination.check(&self.tracker);
+        self.tracker.stop_reason.is_some()
+    }
+
+    pub fn checkpoint(&self, generations: usize) -> Checkpoint<P> {
+        Checkpoint {
+            generations,
+            parameters: self.parameters(),
+            problem: self.problem.clone(),
+            genes: self.genes.clone(),
+            survivors: self.survivors.clone(),
+            rng: self.rng.clone(),
+            tracker: self.tracker.clone(),
         }
+    }
 
-        println!("\n ##################   FIT DONE  ##################\n");
+    pub fn from_checkpoint(checkpoint: Checkpoint<P>) -> Self {
+        let mut model = Self::new(checkpoint.problem);
+
+        model.set_parameters(&checkpoint.parameters);
+        model.genes = checkpoint.genes;
+        model.survivors = checkpoint.survivors;
+        model.rng = checkpoint.rng;
+        model.tracker = checkpoint.tracker;
+
+        // Checkpoints saved during a run still have it running
+        model.tracker.is_running = false;
+        model.tracker.is_paused = false;
+
+        model
     }
 
-    fn run_once(&mut self, generation: usize) {
+    // Failures are reported but do not stop the run
+    fn save_checkpoint(&mut self, generations: usize, is_final: bool) {
+        let path = match &self.checkpoint_path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let is_due = self.checkpoint_interval > 0
+            && (self.tracker.total_generation as usize).is_multiple_of(self.checkpoint_interval);
+
+        if !is_final && !is_due {
+            return;
+        }
+
+        if let Err(err) = self.checkpoint(generations).save(path) {
+            self.log(LogLevel::Error, format!("checkpoint failed: {}", err));
+        }
+    }
+
+    // Messages of the engine go to the observers, never to stdout
+    fn log(&mut self, level: LogLevel, message: String) {
+        let entry = LogEntry::new(level, self.tracker.total_generation, message);
+        self.observers.on_log(&entry);
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let probabilities = [
+            ("mutation_probability", self.mutation_probability),
+            ("mutation_rate", self.mutation_rate),
+            ("elite_conservation_probability", self.elite_conservation_probability),
+            ("total_conservation_probability", self.total_conservation_probability),
+            ("crossover_probability", self.crossover_probability),
+            ("uniform_bias", self.uniform_bias),
+        ];
+
+        for (name, value) in probabilities {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{} must be in 0.0..=1.0 ({})", name, value));
+            }
+        }
+
+        if !(1.0..=2.0).contains(&self.rank_pressure) {
+            return Err(format!("rank_pressure must be in 1.0..=2.0 ({})", self.rank_pressure));
+        }
+
+        if self.gene_len < 2 {
+            return Err(format!("gene_len must be at least 2 ({})", self.gene_len));
+        }
+
+        if ((self.gene_len as f64) * self.total_conservation_probability) < 1.0 {
+            return Err("total_conservation_probability keeps no gene".to_string());
+        }
+
+        if self.problem.gene_data_len() == 0 {
+            return Err("problem has no gene data".to_string());
+        }
+
+        let (min_value, max_value) = self.problem.allele_range();
+        if min_value >= max_value {
+            return Err(format!("allele range is empty ({}..{})", min_value, max_value));
+        }
+
+        self.problem.validate()?;
+
+        self.termination.validate()
+    }
+
+    pub fn parameters(&self) -> GAParameters {
+        GAParameters {
+            gene_len: self.gene_len,
+            mutation_probability: self.mutation_probability,
+            mutation_gene_data_len: self.mutation_gene_data_len,
+            mutation_kind: self.mutation_kind,
+            mutation_rate: self.mutation_rate,
+            mutation_schedule: self.mutation_schedule,
+            mutation_decay: self.mutation_decay,
+            stagnation_generations: self.stagnation_generations,
+            stagnation_boost: self.stagnation_boost,
+            elite_conservation_probability: self.elite_conservation_probability,
+            total_conservation_probability: self.total_conservation_probability,
+            selection_kind: self.selection_kind,
+            tournament_size: self.tournament_size,
+            rank_pressure: self.rank_pressure,
+            crossover_kind: self.crossover_kind,
+            crossover_probability: self.crossover_probability,
+            crossover_points: self.crossover_points,
+            uniform_bias: self.uniform_bias,
+            diversity_metric: self.diversity_metric,
+            diversity_samples: self.diversity_samples,
+            objective_mode: self.objective_mode,
+            termination: self.termination.clone(),
+            seed: self.seed,
+        }
+    }
+
+    pub fn live_parameters(&self) -> LiveParameters {
+        LiveParameters {
+            mutation_probability: self.mutation_probability,
+            mutation_gene_data_len: self.mutation_gene_data_len,
+            mutation_kind: self.mutation_kind,
+            mutation_rate: self.mutation_rate,
+            mutation_schedule: self.mutation_schedule,
+            mutation_decay: self.mutation_decay,
+            stagnation_generations: self.stagnation_generations,
+            stagnation_boost: self.stagnation_boost,
+            elite_conservation_probability: self.elite_conservation_probability,
+            total_conservation_probability: self.total_conservation_probability,
+            selection_kind: self.selection_kind,
+            tournament_size: self.tournament_size,
+            rank_pressure: self.rank_pressure,
+        }
+    }
+
+    pub fn set_parameters(&mut self, parameters: &GAParameters) {
+        self.gene_len = parameters.gene_len;
+        self.mutation_probability = parameters.mutation_probability;
+        self.mutation_gene_data_len = parameters.mutation_gene_data_len;
+        self.mutation_kind = parameters.mutation_kind;
+        self.mutation_rate = parameters.mutation_rate;
+        self.mutation_schedule = parameters.mutation_schedule;
+        self.mutation_decay = parameters.mutation_decay;
+        self.stagnation_generations = parameters.stagnation_generations;
+        self.stagnation_boost = parameters.stagnation_boost;
+        self.elite_conservation_probability = parameters.elite_conservation_probability;
+        self.total_conservation_probability = parameters.total_conservation_probability;
+        self.selection_kind = parameters.selection_kind;
+        self.tournament_size = parameters.tournament_size;
+        self.rank_pressure = parameters.rank_pressure;
+        self.crossover_kind = parameters.crossover_kind;
+        self.crossover_probability = parameters.crossover_probability;
+        self.crossover_points = parameters.crossover_points;
+        self.uniform_bias = parameters.uniform_bias;
+        self.diversity_metric = parameters.diversity_metric;
+        self.diversity_samples = parameters.diversity_samples;
+        self.objective_mode = parameters.objective_mode;
+        self.termination = parameters.termination.clone();
+        self.seed = parameters.seed;
+    }
+
+    pub fn set_live_parameters(&mut self, parameters: &LiveParameters) {
+        self.mutation_probability = parameters.mutation_probability;
+        self.mutation_gene_data_len = parameters.mutation_gene_data_len;
+        self.mutation_kind = parameters.mutation_kind;
+        self.mutation_rate = parameters.mutation_rate;
+        self.mutation_schedule = parameters.mutation_schedule;
+        self.mutation_decay = parameters.mutation_decay;
+        self.stagnation_generations = parameters.stagnation_generations;
+        self.stagnation_boost = parameters.stagnation_boost;
+        self.elite_conservation_probability = parameters.elite_conservation_probability;
+        self.total_conservation_probability = parameters.total_conservation_probability;
+        self.selection_kind = parameters.selection_kind;
+        self.tournament_size = parameters.tournament_size;
+        self.rank_pressure = parameters.rank_pressure;
+    }
+
+    pub(crate) fn start(&mut self) {
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        self.tracker.reset();
+        self.tracker.seed = self.seed;
+        self.tracker.parameters = Some(self.parameters());
+        self.tracker.objective_names = self.problem.objective_names();
+
+        self.survivors.clear();
+        self.shake();
+    }
+
+    pub(crate) fn run_once(&mut self, generation: usize) {
+        let now = Instant::now();
+
         self.set_fitnesses();
 
+        if self.objective_mode == ObjectiveMode::Nsga2 {
+            self.survive();
+        }
+
         let mut average_fitness: f64 = 0.0;
         for n in 0..self.gene_len {
-            average_fitness += self.genes[n].fitness as f64;
+            average_fitness += self.genes[n].fitness;
         }
         average_fitness /= self.gene_len as f64;
 
-        if self.genes[0].fitness < self.tracker.best_gene.fitness {
-            let diff = self.genes[0].fitness - self.tracker.best_gene.fitness;
+        // The first gene unless genes are sorted by front
+        let best = self.genes.iter()
+            .min_by(|a, b| a.fitness.total_cmp(&b.fitness))
+            .unwrap();
+        let best_fitness = best.fitness;
+
+        // No best gene yet before the first generation, whatever the metric
+        let has_best = !self.tracker.best_gene.data.is_empty();
+
+        if !has_best || best.fitness < self.tracker.best_gene.fitness {
+            let diff = match has_best {
+                true => best.fitness - self.tracker.best_gene.fitness,
+                false => 0.0,
+            };
             self.tracker.best_gene = Gene {
-                data: self.genes[0].data.to_vec(),
-                fitness: self.genes[0].fitness
+                data: best.data.to_vec(),
+                fitness: best.fitness,
+                objectives: best.objectives.clone(),
+                ..Gene::default()
             };
+            self.tracker.best_generation = self.tracker.total_generation;
 
-            println!("[Generation] {}", generation);
-            println!(" [Best] fitness: {}, diffence: {}\n", self.tracker.best_gene.fitness, diff);
+            self.observers.on_new_best(generation, diff, &self.tracker);
         }
 
+        // Measured on the evaluated population, before it is replaced
+        let average_diff = self.diversity(generation);
+
+        // The generation is finished once evaluated, so that the mutation
+        // schedule sees the same stagnation as the stopping criteria
+        self.tracker.append(best_fitness, average_fitness, average_diff);
+
         self.selection();
-        
-        self.mutation();
+        self.mutation(generation);
 
-        let mut total_average_diff: f64 = 0.0;
+        self.tracker.elapsed += now.elapsed();
 
-        for y in 0..self.gene_len {
-            let mut average_diff: f64 = 0.0;
+        self.observers.on_generation_end(generation, &self.tracker);
+    }
 
-            for x in 0..self.gene_len {
-                average_diff += (self.genes[y].fitness - self.genes[x].fitness).abs();
-            }
+    // NSGA-II survival: the offspring and the survivors of the last
+    // generation compete, and the best `gene_len` by front and crowding
+    // distance are kept in that order
+    fn survive(&mut self) {
+        let problem = &self.problem;
+        let set_objectives = |gene: &mut Gene| gene.objectives = problem.objectives(gene);
 
-            average_diff /= self.gene_len as f64;
-            total_average_diff += average_diff;
-        }
+        #[cfg(feature = "parallel")]
+        self.genes.par_iter_mut().for_each(set_objectives);
+        #[cfg(not(feature = "parallel"))]
+        self.genes.iter_mut().for_each(set_objectives);
+
+        self.genes.append(&mut self.survivors);
+        let fronts = crowded_sort(&mut self.genes, self.gene_len);
 
-        total_average_diff /= self.gene_len as f64;
-        self.tracker.append(self.genes[0].fitness, average_fitness, total_average_diff);
+        self.survivors = self.genes.clone();
+        self.tracker.set_pareto_front(pareto_front(&self.genes, &fronts));
     }
 
-    fn shake(&mut self) {
-        let max_value: i32 = self.divide.len() as i32;
-        let gene_data_len: usize = self.properties.len();
+    fn diversity(&self, generation: usize) -> f64 {
+        match self.diversity_metric {
+            DiversityMetric::Fitness => fitness_diversity(&self.genes),
+            DiversityMetric::Genotype => {
+                // Own stream so that sampling does not change the evolution
+                let mut rng = ChaCha8Rng::seed_from_u64(self.seed ^ generation as u64);
+                let (min_value, max_value) = self.problem.allele_range();
+
+                genotype_diversity(&self.genes, min_value, max_value, self.diversity_samples, &mut rng)
+            }
+        }
+    }
 
+    fn shake(&mut self) {
         self.genes.clear();
 
         for _n in 0..self.gene_len {
-            let mut new_gene = Gene { data: Vec::<i32>::new(), fitness: 1.0 };
-            new_gene.init(gene_data_len, 0, max_value);
+            let mut new_gene = Gene::default();
+            self.problem.init(&mut new_gene, &mut self.rng);
+            self.problem.repair(&mut new_gene, &mut self.rng);
 
             self.genes.push(new_gene);
         }
     }
 
-    fn set_fitnesses(&mut self) {
+    pub(crate) fn set_fitnesses(&mut self) {
+        let problem = &self.problem;
+        let evaluate = |gene: &mut Gene| problem.evaluate(gene);
 
-        for n in 0..self.gene_len {
-            self.genes[n].set_fitness(&self.divide, &self.properties)
-        }
+        self.tracker.evaluations += self.genes.len() as u64;
+
+        #[cfg(feature = "parallel")]
+        self.genes.par_iter_mut().for_each(evaluate);
+        #[cfg(not(feature = "parallel"))]
+        self.genes.iter_mut().for_each(evaluate);
 
-        self.genes.sort_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap());
+        self.genes.sort_by(|a, b| a.fitness.total_cmp(&b.fitness));
     }
 
     fn selection(&mut self) {
-        let mut rng = rand::thread_rng();
         let mut child = Vec::<Gene>::new();
 
         let mut elite_len: usize = ((self.gene_len as f64) * self.elite_conservation_probability) as usize;
         let total_len: usize = ((self.gene_len as f64) * self.total_conservation_probability) as usize;
 
-        if elite_len % 2 != 0 {
+        if !elite_len.is_multiple_of(2) {
             elite_len -= 1;
         }
 
         // Elite conservation
         for n in 0..elite_len {
-            let new_gene = Gene {
-                data: self.genes[n].data.to_vec(),
-                fitness: self.genes[n].fitness
-            };
-
-            child.push(new_gene);
+            child.push(self.genes[n].clone());
         }
 
         // Gene crossover
-        while child.len() < self.gene_len {
-            let n1 = rng.gen_range(0..total_len);
-            let n2 = rng.gen_range(0..total_len);
+        let strategy = self.selection_strategy(total_len);
+        let operator = self.crossover_operator();
+        let generation_seed = self.rng.gen::<u64>();
+        let pair_len = self.gene_len.saturating_sub(elite_len).div_ceil(2);
+
+        let genes = &self.genes;
+        let problem = &self.problem;
+        let crossover_probability = self.crossover_probability;
 
-            let result = self.genes[n1].uniform_crossover(&self.genes[n2]);
+        let breed = |pair: usize| {
+            let mut rng = stream_rng(generation_seed, pair);
 
-            child.push(result.0);
-            child.push(result.1);
+            let n1 = strategy.select(&mut rng);
+            let n2 = strategy.select(&mut rng);
+
+            // Parents are copied unchanged when crossover does not happen
+            if rng.gen::<f64>() < crossover_probability {
+                let (mut gene1, mut gene2) = operator.crossover(&genes[n1], &genes[n2], &mut rng);
+
+                problem.repair(&mut gene1, &mut rng);
+                problem.repair(&mut gene2, &mut rng);
+
+                (gene1, gene2)
+            } else {
+                (genes[n1].clone(), genes[n2].clone())
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        let pairs: Vec<(Gene, Gene)> = (0..pair_len).into_par_iter().map(breed).collect();
+        #[cfg(not(feature = "parallel"))]
+        let pairs: Vec<(Gene, Gene)> = (0..pair_len).map(breed).collect();
+
+        for (gene1, gene2) in pairs {
+            child.push(gene1);
+            child.push(gene2);
+        }
+
+        // Swap generation. The last pair has one child too many when the
+        // genes left after the elite are odd.
+        child.truncate(self.gene_len);
+        self.genes = child;
+    }
+
+    fn selection_strategy(&self, total_len: usize) -> Box<dyn SelectionStrategy> {
+        match self.selection_kind {
+            SelectionKind::Truncation => Box::new(Truncation { total_len }),
+            SelectionKind::Tournament => Box::new(Tournament { k: self.tournament_size, len: self.gene_len }),
+            SelectionKind::Roulette => Box::new(Weighted::roulette(&self.genes)),
+            SelectionKind::Rank => Box::new(Weighted::rank(self.gene_len, self.rank_pressure)),
         }
+    }
 
-        // Swap generation
-        self.genes = (&child[0..self.gene_len]).to_vec();
+    fn crossover_operator(&self) -> Box<dyn CrossoverOperator> {
+        match self.crossover_kind {
+            CrossoverKind::OnePoint => Box::new(OnePoint),
+            CrossoverKind::TwoPoint => Box::new(TwoPoint),
+            CrossoverKind::KPoint => Box::new(KPoint { k: self.crossover_points }),
+            CrossoverKind::Uniform => Box::new(Uniform { bias: self.uniform_bias }),
+        }
+    }
 
-        if child.len() != self.gene_len {
-            println!("  [ERROR] child length is wrong. ({})", child.len());
+    fn mutation_operator(&self, factor: f64) -> Box<dyn MutationOperator<P>> {
+        match self.mutation_kind {
+            MutationKind::RandomReset => Box::new(RandomReset { count: self.mutation_gene_data_len }),
+            MutationKind::Bernoulli => Box::new(Bernoulli { rate: (self.mutation_rate * factor).min(1.0) }),
+            MutationKind::Swap => Box::new(Swap { count: self.mutation_gene_data_len }),
+            MutationKind::MoveToUnderAllocated => Box::new(MoveToUnderAllocated { count: self.mutation_gene_data_len }),
         }
     }
 
-    fn mutation(&mut self) {
-        let mut rng = rand::thread_rng();
+    fn mutation(&mut self, generation: usize) {
+        let factor = self.mutation_schedule.factor(
+            generation,
+            self.tracker.stagnation(),
+            self.mutation_decay,
+            self.stagnation_generations,
+            self.stagnation_boost,
+        );
 
-        let max_value: i32 = self.divide.len() as i32;
+        let operator = self.mutation_operator(factor);
         let elite_len: usize = ((self.gene_len as f64) * self.elite_conservation_probability) as usize;
-        let mutation_len: i32 = ((self.gene_len as f64) * self.mutation_probability) as i32;
+        let candidate_len: usize = self.gene_len.saturating_sub(elite_len);
+        let mutation_probability = (self.mutation_probability * factor).min(1.0);
+        let mutation_len: usize = ((self.gene_len as f64) * mutation_probability) as usize;
 
-        for _n in 0..mutation_len {
-            let index = rng.gen_range(elite_len..self.gene_len);
-            self.genes[index] = self.genes[index].mutation(self.mutation_gene_data_len, 0, max_value);
+        // Every non-elite gene is mutated at most once
+        let mut is_chosen = vec![false; candidate_len];
+
+        for index in sample(&mut self.rng, candidate_len, mutation_len.min(candidate_len)) {
+            is_chosen[index] = true;
         }
+
+        let generation_seed = self.rng.gen::<u64>();
+        let problem = &self.problem;
+
+        let mutate = |(index, gene): (usize, &mut Gene)| {
+            if is_chosen[index] {
+                let mut rng = stream_rng(generation_seed, index);
+
+                operator.mutate(gene, problem, &mut rng);
+                problem.repair(gene, &mut rng);
+            }
+        };
+
+        let candidates = &mut self.genes[elite_len..elite_len + candidate_len];
+
+        #[cfg(feature = "parallel")]
+        candidates.par_iter_mut().enumerate().for_each(mutate);
+        #[cfg(not(feature = "parallel"))]
+        candidates.iter_mut().enumerate().for_each(mutate);
     }
 }
 
-#[derive(Clone)]
+impl<P: Problem> Controlled for GAModel<P> {
+    fn parts_mut(&mut self) -> (&mut GAModelTracker, &mut Observers) {
+        (&mut self.tracker, &mut self.observers)
+    }
+
+    fn set_live_parameters(&mut self, parameters: &LiveParameters) {
+        GAModel::set_live_parameters(self, parameters);
+    }
+
+    fn start(&mut self) {
+        GAModel::start(self);
+    }
+
+    fn run_once(&mut self, generation: usize) {
+        GAModel::run_once(self, generation);
+    }
+
+    fn check_termination(&mut self) -> bool {
+        GAModel::check_termination(self)
+    }
+
+    fn save_checkpoint(&mut self, generations: usize, is_final: bool) {
+        GAModel::save_checkpoint(self, generations, is_final);
+    }
+}
+
+// Every offspring draws from its own stream of the generation seed, so the
+// results do not depend on how the work is split between threads
+fn stream_rng(seed: u64, stream: usize) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream as u64);
+    rng
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Gene {
     pub data: Vec<i32>,
     pub fitness: f64,
+    // Set in `ObjectiveMode::Nsga2` only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objectives: Vec<f64>,
+    // Problem state cached for delta evaluation (per-heir totals of
+    // `PropertyDistribution`), empty when it has to be rebuilt
+    #[serde(skip)]
+    pub allocation: Vec<i32>,
+    // (index, old allele, new allele) since `allocation` was last updated
+    #[serde(skip)]
+    pub changes: Vec<(usize, i32, i32)>,
 }
 
 impl Default for Gene {
@@ -312,132 +927,270 @@ impl Default for Gene {
         Self {
             data: Vec::<i32>::new(),
             fitness: 1.0,
+            objectives: Vec::new(),
+            allocation: Vec::new(),
+            changes: Vec::new(),
         }
     }
 }
 
 impl Gene {
-    fn init(&mut self, data_len: usize, min_value: i32, max_value: i32) {
-        let mut rng = rand::thread_rng();
-
+    pub fn init(&mut self, data_len: usize, min_value: i32, max_value: i32, rng: &mut ChaCha8Rng) {
         self.data.clear();
+        self.allocation.clear();
+        self.changes.clear();
+
         for _n in 0..data_len {
             self.data.push(rng.gen_range(min_value..max_value));
         }
     }
 
-    fn set_fitness(&mut self, divide: &[f64], properties: &[i32]) {
-        let len = self.data.len();
-        let data_num = divide.len();
-        let properties_sum: i32 = properties.iter().sum();
-
-        let mut data_divide: Vec<i32> = vec![0; data_num];
+    // Changes one locus, keeping track of it for delta evaluation
+    pub fn set_allele(&mut self, index: usize, allele: i32) {
+        let old_allele = self.data[index];
 
-        for n in 0..len {
-            data_divide[self.data[n] as usize] += properties[n];
+        if old_allele == allele {
+            return;
         }
 
-        self.fitness = 0.0;
+        self.data[index] = allele;
+
+        if !self.allocation.is_empty() {
+            self.changes.push((index, old_allele, allele));
 
-        for n in 0..data_num {
-            let ratio: f64 = (data_divide[n] as f64) / (properties_sum as f64);
-            self.fitness += (divide[n] - ratio).abs();
+            // A full evaluation is cheaper past this point
+            if self.changes.len() * 4 >= self.data.len() {
+                self.allocation.clear();
+                self.changes.clear();
+            }
         }
     }
 
-    fn crossover(&self, another: &Gene) -> (Gene, Gene) {
+    pub fn crossover(&self, another: &Gene, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
         let len = self.data.len();
-        let mut rng = rand::thread_rng();
 
-        let p1 = rng.gen_range(0..len);
-        let p2 = rng.gen_range(0..len);
+        let mut p1 = rng.gen_range(0..len);
+        let mut p2 = rng.gen_range(0..len);
 
-        let mut g1 = Gene { data: vec![0; len], fitness: -1.0 };
-        let mut g2 = Gene { data: vec![0; len], fitness: -1.0 };
+        if p1 > p2 {
+            std::mem::swap(&mut p1, &mut p2);
+        }
 
-        for n in 0..len {
-            if n >= p1 && n <= p2 {
-                g1.data[n] = self.data[n];
-                g2.data[n] = another.data[n];
-            } else {
-                g1.data[n] = another.data[n];
-                g2.data[n] = self.data[n];
-            }
+        // Children start from one parent each and take the other's loci
+        // outside p1..=p2
+        let mut g1 = self.clone();
+        let mut g2 = another.clone();
+
+        for n in (0..p1).chain((p2 + 1)..len) {
+            g1.set_allele(n, another.data[n]);
+            g2.set_allele(n, self.data[n]);
         }
 
         (g1, g2)
     }
 
-    fn uniform_crossover(&self, another: &Gene) -> (Gene, Gene) {
+    pub fn one_point_crossover(&self, another: &Gene, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
+        self.k_point_crossover(another, 1, rng)
+    }
+
+    pub fn k_point_crossover(&self, another: &Gene, k: usize, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
         let len = self.data.len();
-        let mut rng = rand::thread_rng();
 
-        let mut mask = vec![false; len];
-        for n in 0..len {
-            let dice: f64 = rng.gen();
-            mask[n] = dice > 0.5;
-        }
+        // Cut points are taken from 1..len so that no segment is empty
+        let mut points: Vec<usize> = match len {
+            0 | 1 => Vec::new(),
+            _ => sample(rng, len - 1, k.min(len - 1)).into_iter().map(|point| point + 1).collect(),
+        };
+        points.sort_unstable();
 
-        let mut g1 = Gene { data: vec![0; len], fitness: -1.0 };
-        let mut g2 = Gene { data: vec![0; len], fitness: -1.0 };
+        let mut g1 = self.clone();
+        let mut g2 = another.clone();
+
+        let mut swap = false;
+        let mut next_point = points.iter().peekable();
 
         for n in 0..len {
-            if mask[n] {
-                g1.data[n] = another.data[n];
-                g2.data[n] = self.data[n];
-            } else {
-                g1.data[n] = self.data[n];
-                g2.data[n] = another.data[n];
+            if next_point.next_if(|point| **point == n).is_some() {
+                swap = !swap;
+            }
+
+            if swap {
+                g1.set_allele(n, another.data[n]);
+                g2.set_allele(n, self.data[n]);
             }
         }
 
         (g1, g2)
     }
 
-    fn average_crossover(&self, another: &Gene) -> Gene {
+    // Each locus of the first child comes from `self` with probability `bias`
+    pub fn uniform_crossover(&self, another: &Gene, bias: f64, rng: &mut ChaCha8Rng) -> (Gene, Gene) {
         let len = self.data.len();
 
-        let mut new_gene = Gene { data: vec![0; len], fitness: -1.0 };
+        let mask: Vec<bool> = (0..len).map(|_n| {
+            let dice: f64 = rng.gen();
+            dice >= bias
+        }).collect();
 
-        for n in 0..len {
-            new_gene.data[n] = (self.data[n] + another.data[n]) / 2;
+        let mut g1 = self.clone();
+        let mut g2 = another.clone();
+
+        for (n, swap) in mask.iter().enumerate() {
+            if *swap {
+                g1.set_allele(n, another.data[n]);
+                g2.set_allele(n, self.data[n]);
+            }
         }
 
-        new_gene
+        (g1, g2)
     }
 
-    fn mutation(&self, count: usize, min_value: i32, max_value: i32) -> Gene {
+    pub fn mutation(&self, count: usize, min_value: i32, max_value: i32, rng: &mut ChaCha8Rng) -> Gene {
         let len = self.data.len();
-        let mut rng = rand::thread_rng();
 
-        let mut gene = Gene {
-            data: self.data.clone(),
-            fitness: 0.0,
-        };
+        let mut gene = self.clone();
+        gene.fitness = 0.0;
 
         for _n in 0..count {
             let index = rng.gen_range(0..len);
-            gene.data[index] = rng.gen_range(min_value..max_value);
+            gene.set_allele(index, rng.gen_range(min_value..max_value));
         }
 
         gene
     }
 
-    fn compare(&self, another: &Gene, data_num: usize) -> i32 {
+    // Differences in how many loci each allele takes (alleles
+    // min_value..max_value)
+    pub fn compare(&self, another: &Gene, min_value: i32, max_value: i32) -> i32 {
         let len = self.data.len();
-        let mut data_count: Vec<i32> = vec![0; data_num];
+        let mut data_count: Vec<i32> = vec![0; (max_value - min_value) as usize];
 
         for n in 0..len {
-            data_count[self.data[n] as usize] += 1;
-            data_count[another.data[n] as usize] -= 1;
+            data_count[(self.data[n] - min_value) as usize] += 1;
+            data_count[(another.data[n] - min_value) as usize] -= 1;
+        }
+
+        data_count.iter().map(|count| count.abs()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best_gene(seed: u64, objective_mode: ObjectiveMode) -> Gene {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let properties = (0..100).map(|_n| rng.gen_range(1..1000)).collect();
+
+        let mut model = GAModel::new(PropertyDistribution::new(vec![0.1; 10], properties));
+        model.gene_len = 100;
+        model.objective_mode = objective_mode;
+        model.seed = seed;
+        model.run(30);
+
+        model.tracker.best_gene
+    }
+
+    #[test]
+    fn same_seed_same_best_gene() {
+        for objective_mode in ObjectiveMode::ALL {
+            let (gene1, gene2) = (best_gene(11, objective_mode), best_gene(11, objective_mode));
+
+            assert_eq!(gene1.data, gene2.data);
+            assert_eq!(gene1.fitness.to_bits(), gene2.fitness.to_bits());
         }
+    }
+
+    // Every offspring has its own random stream, so the thread count does
+    // not matter
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn same_best_gene_serial_and_parallel() {
+        let pool = |threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
 
-        let mut diff: i32 = 0;
+        for objective_mode in ObjectiveMode::ALL {
+            let serial = pool(1).install(|| best_gene(11, objective_mode));
+            let parallel = pool(4).install(|| best_gene(11, objective_mode));
 
-        for n in 0..data_num {
-            diff += data_count[n].abs();
+            assert_eq!(serial.data, parallel.data);
+            assert_eq!(serial.fitness.to_bits(), parallel.fitness.to_bits());
         }
+    }
+
+    fn gene(data: Vec<i32>) -> Gene {
+        Gene { data, ..Gene::default() }
+    }
+
+    fn append_generations(tracker: &mut GAModelTracker, generations: std::ops::Range<usize>) {
+        for generation in generations {
+            let value = generation as f64;
+            tracker.append(value, value * 2.0, value * 3.0);
+
+            for (n, changes) in tracker.island_best_fitness_changes.iter_mut().enumerate() {
+                changes.push(value + n as f64);
+            }
+        }
+    }
+
+    fn assert_same_history(receiver: &GAModelTracker, sender: &GAModelTracker) {
+        assert_eq!(receiver.total_generation, sender.total_generation);
+        assert_eq!(receiver.best_fitness_changes, sender.best_fitness_changes);
+        assert_eq!(receiver.average_fitness_changes, sender.average_fitness_changes);
+        assert_eq!(receiver.average_diff_changes, sender.average_diff_changes);
+        assert_eq!(receiver.island_best_fitness_changes, sender.island_best_fitness_changes);
+    }
+
+    #[test]
+    fn apply_rebuilds_the_sender_history() {
+        let mut sender = GAModelTracker {
+            island_best_fitness_changes: vec![Vec::new(); 2],
+            best_gene: gene(vec![1, 2, 3]),
+            ..GAModelTracker::default()
+        };
+        let mut receiver = GAModelTracker::default();
+
+        append_generations(&mut sender, 0..5);
+        receiver.apply(sender.update_since(0, 0, true, false));
+        assert_same_history(&receiver, &sender);
+        assert_eq!(receiver.best_gene.data, vec![1, 2, 3]);
+
+        // Without the best gene the receiver keeps its own
+        append_generations(&mut sender, 5..8);
+        sender.best_gene = gene(vec![4, 5, 6]);
+        receiver.apply(sender.update_since(5, 0, false, false));
+        assert_same_history(&receiver, &sender);
+        assert_eq!(receiver.best_gene.data, vec![1, 2, 3]);
+
+        // Generations sent again replace the ones the receiver has
+        receiver.apply(sender.update_since(3, 0, true, false));
+        assert_same_history(&receiver, &sender);
+        assert_eq!(receiver.best_gene.data, vec![4, 5, 6]);
+    }
 
-        diff
+    #[test]
+    fn apply_rebuilds_the_parameter_changes_and_pareto_front() {
+        let parameters = GAModel::new(PropertyDistribution::new(vec![0.5; 2], vec![1, 2])).live_parameters();
+        let change = |generation| ParameterChange { generation, parameters: parameters.clone() };
+
+        let mut sender = GAModelTracker::default();
+        let mut receiver = GAModelTracker::default();
+
+        sender.parameter_changes.push(change(1));
+        sender.set_pareto_front(vec![gene(vec![0, 1])]);
+        receiver.apply(sender.update_since(0, 0, false, true));
+        assert_eq!(receiver.parameter_changes, sender.parameter_changes);
+        assert_eq!(receiver.pareto_front[0].data, vec![0, 1]);
+
+        // Without the front the receiver keeps its own
+        sender.parameter_changes.push(change(4));
+        sender.set_pareto_front(vec![gene(vec![1, 0]), gene(vec![1, 1])]);
+        receiver.apply(sender.update_since(0, 1, false, false));
+        assert_eq!(receiver.parameter_changes, sender.parameter_changes);
+        assert_eq!(receiver.pareto_front.len(), 1);
+
+        receiver.apply(sender.update_since(0, 0, false, true));
+        assert_eq!(receiver.parameter_changes, sender.parameter_changes);
+        assert_eq!(receiver.pareto_front_version, sender.pareto_front_version);
+        assert_eq!(receiver.pareto_front.len(), 2);
     }
 }
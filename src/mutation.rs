@@ -0,0 +1,144 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use rand::prelude::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+
+use super::model::Gene;
+use super::problem::Problem;
+
+pub trait MutationOperator<P: Problem>: Sync {
+    fn mutate(&self, gene: &mut Gene, problem: &P, rng: &mut ChaCha8Rng);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MutationKind {
+    RandomReset,
+    Bernoulli,
+    Swap,
+    MoveToUnderAllocated,
+}
+
+named_enum!(MutationKind, "mutation operator", {
+    RandomReset => "random-reset",
+    Bernoulli => "bernoulli",
+    Swap => "swap",
+    MoveToUnderAllocated => "move-to-under-allocated",
+});
+
+// Resets `count` random loci to uniform alleles
+pub struct RandomReset {
+    pub count: usize,
+}
+
+impl<P: Problem> MutationOperator<P> for RandomReset {
+    fn mutate(&self, gene: &mut Gene, problem: &P, rng: &mut ChaCha8Rng) {
+        let (min_value, max_value) = problem.allele_range();
+        *gene = gene.mutation(self.count, min_value, max_value, rng);
+    }
+}
+
+// Resets every locus independently with probability `rate`
+pub struct Bernoulli {
+    pub rate: f64,
+}
+
+impl<P: Problem> MutationOperator<P> for Bernoulli {
+    fn mutate(&self, gene: &mut Gene, problem: &P, rng: &mut ChaCha8Rng) {
+        let (min_value, max_value) = problem.allele_range();
+
+        for index in 0..gene.data.len() {
+            if rng.gen::<f64>() < self.rate {
+                gene.set_allele(index, rng.gen_range(min_value..max_value));
+            }
+        }
+    }
+}
+
+// Exchanges the owners of two loci, `count` times
+pub struct Swap {
+    pub count: usize,
+}
+
+impl<P: Problem> MutationOperator<P> for Swap {
+    fn mutate(&self, gene: &mut Gene, _problem: &P, rng: &mut ChaCha8Rng) {
+        let len = gene.data.len();
+
+        if len < 2 {
+            return;
+        }
+
+        for _n in 0..self.count {
+            let index1 = rng.gen_range(0..len);
+            let index2 = rng.gen_range(0..len);
+
+            let (allele1, allele2) = (gene.data[index1], gene.data[index2]);
+            gene.set_allele(index1, allele2);
+            gene.set_allele(index2, allele1);
+        }
+    }
+}
+
+// Hands `count` random loci to the most under-allocated owner.
+// Problems without such a notion are left unchanged.
+pub struct MoveToUnderAllocated {
+    pub count: usize,
+}
+
+impl<P: Problem> MutationOperator<P> for MoveToUnderAllocated {
+    fn mutate(&self, gene: &mut Gene, problem: &P, rng: &mut ChaCha8Rng) {
+        let len = gene.data.len();
+
+        if len == 0 {
+            return;
+        }
+
+        for _n in 0..self.count {
+            let target = match problem.most_under_allocated(&gene.data) {
+                Some(target) => target,
+                None => return,
+            };
+
+            let index = rng.gen_range(0..len);
+            gene.set_allele(index, target);
+        }
+    }
+}
+
+// Scales the mutation rates over a run
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MutationSchedule {
+    Constant,
+    Decay,
+    Adaptive,
+}
+
+named_enum!(MutationSchedule, "mutation schedule", {
+    Constant => "constant",
+    Decay => "decay",
+    Adaptive => "adaptive",
+});
+
+impl MutationSchedule {
+    //  - Decay: rate * decay^generation
+    //  - Adaptive: rate * boost once the best gene has not improved for
+    //    `stagnation_generations` generations
+    pub fn factor(&self, generation: usize, stagnation: usize, decay: f64, stagnation_generations: usize, boost: f64) -> f64 {
+        match self {
+            MutationSchedule::Constant => 1.0,
+            MutationSchedule::Decay => decay.powf(generation as f64),
+            MutationSchedule::Adaptive => {
+                if stagnation >= stagnation_generations {
+                    boost
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::cmp::Ordering;
+
+use serde::{Serialize, Deserialize};
+
+use super::model::Gene;
+
+// How genes are compared
+//  - Single: by fitness
+//  - Nsga2: by Pareto front, then crowding distance, on the objectives of
+//    the problem (NSGA-II)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ObjectiveMode {
+    Single,
+    Nsga2,
+}
+
+named_enum!(ObjectiveMode, "objective mode", {
+    Single => "single",
+    Nsga2 => "nsga2",
+});
+
+// No worse in every objective and better in at least one (lower is better)
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut is_better = false;
+
+    for (a, b) in a.iter().zip(b.iter()) {
+        if a > b {
+            return false;
+        }
+
+        if a < b {
+            is_better = true;
+        }
+    }
+
+    is_better
+}
+
+// Front of every point, 0 being the non-dominated one (fast non-dominated
+// sort, O(objectives * n²))
+pub fn non_dominated_sort(points: &[&[f64]]) -> Vec<usize> {
+    let len = points.len();
+    let mut dominated_by: Vec<Vec<usize>> = vec![Vec::new(); len];
+    let mut domination_count: Vec<usize> = vec![0; len];
+
+    for p in 0..len {
+        for q in (p + 1)..len {
+            if dominates(points[p], points[q]) {
+                dominated_by[p].push(q);
+                domination_count[q] += 1;
+            } else if dominates(points[q], points[p]) {
+                dominated_by[q].push(p);
+                domination_count[p] += 1;
+            }
+        }
+    }
+
+    let mut fronts: Vec<usize> = vec![0; len];
+    let mut current: Vec<usize> = (0..len).filter(|p| domination_count[*p] == 0).collect();
+    let mut front = 0;
+
+    while !current.is_empty() {
+        let mut next = Vec::new();
+
+        for p in current {
+            fronts[p] = front;
+
+            for q in dominated_by[p].iter() {
+                domination_count[*q] -= 1;
+
+                if domination_count[*q] == 0 {
+                    next.push(*q);
+                }
+            }
+        }
+
+        current = next;
+        front += 1;
+    }
+
+    fronts
+}
+
+// Crowding distance of every point within its front. The ends of a front
+// are infinitely far so that they are always kept.
+pub fn crowding_distance(points: &[&[f64]], fronts: &[usize]) -> Vec<f64> {
+    let len = points.len();
+    let objective_len = points.first().map(|point| point.len()).unwrap_or(0);
+    let front_len = fronts.iter().max().map(|front| front + 1).unwrap_or(0);
+    let mut distances: Vec<f64> = vec![0.0; len];
+    let value = |p: usize, objective: usize| points[p][objective];
+
+    for front in 0..front_len {
+        let mut members: Vec<usize> = (0..len).filter(|p| fronts[*p] == front).collect();
+
+        for objective in 0..objective_len {
+            members.sort_by(|a, b| value(*a, objective).total_cmp(&value(*b, objective)));
+
+            let first = members[0];
+            let last = members[members.len() - 1];
+            let range = value(last, objective) - value(first, objective);
+
+            distances[first] = f64::INFINITY;
+            distances[last] = f64::INFINITY;
+
+            if range <= 0.0 {
+                continue;
+            }
+
+            for window in members.windows(3) {
+                distances[window[1]] += (value(window[2], objective) - value(window[0], objective)) / range;
+            }
+        }
+    }
+
+    distances
+}
+
+// Keeps the `len` best genes by front, then by crowding distance, in that
+// order. Returns the front of every kept gene.
+pub fn crowded_sort(genes: &mut Vec<Gene>, len: usize) -> Vec<usize> {
+    let points: Vec<&[f64]> = genes.iter().map(|gene| gene.objectives.as_slice()).collect();
+    let fronts = non_dominated_sort(&points);
+    let distances = crowding_distance(&points, &fronts);
+
+    let mut order: Vec<usize> = (0..genes.len()).collect();
+    order.sort_by(|a, b| match fronts[*a].cmp(&fronts[*b]) {
+        Ordering::Equal => distances[*b].total_cmp(&distances[*a]),
+        ordering => ordering,
+    });
+    order.truncate(len);
+
+    let mut taken: Vec<Option<Gene>> = std::mem::take(genes).into_iter().map(Some).collect();
+    *genes = order.iter().map(|n| taken[*n].take().unwrap()).collect();
+
+    order.iter().map(|n| fronts[*n]).collect()
+}
+
+// Distinct objective vectors of the first front, sorted by objectives
+pub fn pareto_front(genes: &[Gene], fronts: &[usize]) -> Vec<Gene> {
+    let mut front: Vec<Gene> = genes.iter()
+        .zip(fronts.iter())
+        .filter(|(_gene, front)| **front == 0)
+        .map(|(gene, _front)| Gene {
+            data: gene.data.clone(),
+            fitness: gene.fitness,
+            objectives: gene.objectives.clone(),
+            ..Gene::default()
+        })
+        .collect();
+
+    front.sort_by(|a, b| {
+        a.objectives.iter()
+            .zip(b.objectives.iter())
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+    front.dedup_by(|a, b| a.objectives == b.objectives);
+
+    front
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Front 0: (1, 5), (2, 3), (4, 1). (3, 4) is dominated by (2, 3) alone,
+    // (4, 4) by (3, 4) as well and (5, 5) by every other point.
+    const POINTS: [[f64; 2]; 6] = [[1.0, 5.0], [2.0, 3.0], [4.0, 1.0], [3.0, 4.0], [5.0, 5.0], [4.0, 4.0]];
+
+    #[test]
+    fn dominates_needs_one_better_objective() {
+        assert!(dominates(&[2.0, 3.0], &[3.0, 4.0]));
+        assert!(dominates(&[3.0, 4.0], &[4.0, 4.0]));
+        assert!(!dominates(&[1.0, 5.0], &[2.0, 3.0]));
+        assert!(!dominates(&[2.0, 3.0], &[2.0, 3.0]));
+    }
+
+    #[test]
+    fn non_dominated_sort_of_known_points() {
+        let points: Vec<&[f64]> = POINTS.iter().map(|point| point.as_slice()).collect();
+
+        assert_eq!(non_dominated_sort(&points), vec![0, 0, 0, 1, 3, 2]);
+    }
+
+    #[test]
+    fn crowding_distance_of_known_points() {
+        let points: Vec<&[f64]> = POINTS.iter().map(|point| point.as_slice()).collect();
+        let fronts = non_dominated_sort(&points);
+
+        // (2, 3) is the only inner point: (4 - 1) / 3 + (5 - 1) / 4
+        let infinity = f64::INFINITY;
+        assert_eq!(crowding_distance(&points, &fronts), vec![infinity, 2.0, infinity, infinity, infinity, infinity]);
+    }
+}
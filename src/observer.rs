@@ -0,0 +1,165 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::sync::mpsc::Sender;
+use std::time::{Instant, Duration};
+
+use super::model::{GAModelTracker, TrackerUpdate};
+use super::termination::StopReason;
+use super::control::ParameterChange;
+use super::log::{LogEntry, LogLevel};
+
+// Hooks called by a running model, all of them empty by default
+//  - on_start: before the first generation of a run or of a resumed run
+//  - on_new_best: `improvement` is the (negative) fitness difference to the
+//    previous best gene, 0 for the first one
+//  - on_finish: after the last generation, with `stop_reason` set
+//  - on_pause, on_parameters_changed: commands of a background fit
+//  - on_log: any other message of the engine
+pub trait Observer: Send {
+    fn on_start(&mut self, _tracker: &GAModelTracker) {}
+    fn on_generation_end(&mut self, _generation: usize, _tracker: &GAModelTracker) {}
+    fn on_new_best(&mut self, _generation: usize, _improvement: f64, _tracker: &GAModelTracker) {}
+    fn on_finish(&mut self, _tracker: &GAModelTracker) {}
+    fn on_pause(&mut self, _tracker: &GAModelTracker) {}
+    fn on_parameters_changed(&mut self, _change: &ParameterChange, _tracker: &GAModelTracker) {}
+    fn on_log(&mut self, _entry: &LogEntry) {}
+}
+
+// Observers registered on a model. A clone of a model has none, so that the
+// copies made for islands never report twice.
+#[derive(Default)]
+pub struct Observers(Vec<Box<dyn Observer>>);
+
+impl Observers {
+    pub fn add(&mut self, observer: impl Observer + 'static) {
+        self.0.push(Box::new(observer));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Observer for Observers {
+    fn on_start(&mut self, tracker: &GAModelTracker) {
+        self.0.iter_mut().for_each(|observer| observer.on_start(tracker));
+    }
+
+    fn on_generation_end(&mut self, generation: usize, tracker: &GAModelTracker) {
+        self.0.iter_mut().for_each(|observer| observer.on_generation_end(generation, tracker));
+    }
+
+    fn on_new_best(&mut self, generation: usize, improvement: f64, tracker: &GAModelTracker) {
+        self.0.iter_mut().for_each(|observer| observer.on_new_best(generation, improvement, tracker));
+    }
+
+    fn on_finish(&mut self, tracker: &GAModelTracker) {
+        self.0.iter_mut().for_each(|observer| observer.on_finish(tracker));
+    }
+
+    fn on_pause(&mut self, tracker: &GAModelTracker) {
+        self.0.iter_mut().for_each(|observer| observer.on_pause(tracker));
+    }
+
+    fn on_parameters_changed(&mut self, change: &ParameterChange, tracker: &GAModelTracker) {
+        self.0.iter_mut().for_each(|observer| observer.on_parameters_changed(change, tracker));
+    }
+
+    fn on_log(&mut self, entry: &LogEntry) {
+        self.0.iter_mut().for_each(|observer| observer.on_log(entry));
+    }
+}
+
+// Prints every new best gene and the end of the run, and the warnings and
+// errors to stderr
+pub struct ConsoleObserver;
+
+impl Observer for ConsoleObserver {
+    fn on_new_best(&mut self, generation: usize, improvement: f64, tracker: &GAModelTracker) {
+        println!("[Generation] {}", generation);
+        println!(" [Best] fitness: {}, diffence: {}\n", tracker.best_gene.fitness, improvement);
+    }
+
+    fn on_finish(&mut self, tracker: &GAModelTracker) {
+        match tracker.stop_reason {
+            Some(StopReason::Stopped) => println!("STOPPED"),
+            _ => println!("Done."),
+        }
+    }
+
+    fn on_log(&mut self, entry: &LogEntry) {
+        if entry.level >= LogLevel::Warn {
+            eprintln!("[{}] {}", entry.level, entry.message);
+        }
+    }
+}
+
+// Sends the changes of the tracker to another thread at most once per
+// `interval`, and always when the run ends or a command changes it. A closed
+// receiver is ignored.
+pub struct ChannelObserver {
+    sender: Sender<TrackerUpdate>,
+    interval: Duration,
+    last_sent: Instant,
+    // What the receiver already has
+    sent_generation: usize,
+    sent_parameter_changes: usize,
+    sent_best_generation: Option<i32>,
+    sent_pareto_front_version: Option<u64>,
+}
+
+impl ChannelObserver {
+    pub fn new(sender: Sender<TrackerUpdate>, interval: Duration) -> Self {
+        Self {
+            sender,
+            interval,
+            last_sent: Instant::now(),
+            sent_generation: 0,
+            sent_parameter_changes: 0,
+            sent_best_generation: None,
+            sent_pareto_front_version: None,
+        }
+    }
+
+    fn send(&mut self, tracker: &GAModelTracker) {
+        let with_best_gene = self.sent_best_generation != Some(tracker.best_generation);
+        let with_pareto_front = self.sent_pareto_front_version != Some(tracker.pareto_front_version);
+
+        self.sender.send(tracker.update_since(self.sent_generation, self.sent_parameter_changes, with_best_gene, with_pareto_front)).ok();
+
+        self.sent_generation = tracker.best_fitness_changes.len();
+        self.sent_parameter_changes = tracker.parameter_changes.len();
+        self.sent_best_generation = Some(tracker.best_generation);
+        self.sent_pareto_front_version = Some(tracker.pareto_front_version);
+        self.last_sent = Instant::now();
+    }
+}
+
+impl Observer for ChannelObserver {
+    fn on_generation_end(&mut self, _generation: usize, tracker: &GAModelTracker) {
+        if self.last_sent.elapsed() >= self.interval {
+            self.send(tracker);
+        }
+    }
+
+    fn on_finish(&mut self, tracker: &GAModelTracker) {
+        self.send(tracker);
+    }
+
+    fn on_pause(&mut self, tracker: &GAModelTracker) {
+        self.send(tracker);
+    }
+
+    fn on_parameters_changed(&mut self, _change: &ParameterChange, tracker: &GAModelTracker) {
+        self.send(tracker);
+    }
+}
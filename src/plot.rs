@@ -0,0 +1,167 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use plotters::prelude::*;
+use plotters::coord::Shift;
+use plotters::style::register_font;
+
+// Tried in order when no font is given
+const FONT_CANDIDATES: [&str; 6] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+static FONT: OnceLock<&'static [u8]> = OnceLock::new();
+
+use super::model::GAModelTracker;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlotSeries {
+    BestFitness,
+    AverageFitness,
+    AverageDiff,
+}
+
+named_enum!(PlotSeries, "plot series", {
+    BestFitness => "best",
+    AverageFitness => "average",
+    AverageDiff => "diff",
+});
+
+impl PlotSeries {
+    fn values<'a>(&self, tracker: &'a GAModelTracker) -> &'a [f64] {
+        match self {
+            PlotSeries::BestFitness => &tracker.best_fitness_changes,
+            PlotSeries::AverageFitness => &tracker.average_fitness_changes,
+            PlotSeries::AverageDiff => &tracker.average_diff_changes,
+        }
+    }
+
+    fn color(&self) -> RGBColor {
+        match self {
+            PlotSeries::BestFitness => BLUE,
+            PlotSeries::AverageFitness => RED,
+            PlotSeries::AverageDiff => GREEN,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PlotOptions {
+    pub width: u32,
+    pub height: u32,
+    pub title: Option<String>,
+    pub series: Vec<PlotSeries>,
+    pub font: Option<PathBuf>,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            title: None,
+            series: PlotSeries::ALL.to_vec(),
+            font: None,
+        }
+    }
+}
+
+// Renders the tracked series to PNG, or to SVG when `path` ends with .svg
+pub fn plot(tracker: &GAModelTracker, path: &Path, options: &PlotOptions) -> Result<(), Box<dyn Error>> {
+    load_font(options.font.as_deref())?;
+
+    let size = (options.width, options.height);
+    let is_svg = path.extension().map(|ext| ext.eq_ignore_ascii_case("svg")).unwrap_or(false);
+
+    if is_svg {
+        draw(SVGBackend::new(path, size).into_drawing_area(), tracker, options)
+    } else {
+        draw(BitMapBackend::new(path, size).into_drawing_area(), tracker, options)
+    }
+}
+
+// The first font loaded is kept for the rest of the process
+fn load_font(font: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    if FONT.get().is_some() {
+        return Ok(());
+    }
+
+    let bytes = match font {
+        Some(font) => fs::read(font)?,
+        None => FONT_CANDIDATES.iter()
+            .find_map(|candidate| fs::read(candidate).ok())
+            .ok_or("no system font found, pass a .ttf font file")?,
+    };
+
+    let bytes = FONT.get_or_init(|| Box::leak(bytes.into_boxed_slice()));
+    register_font("sans-serif", FontStyle::Normal, bytes).map_err(|_err| "invalid font file")?;
+
+    Ok(())
+}
+
+fn draw<DB>(plt: DrawingArea<DB, Shift>, tracker: &GAModelTracker, options: &PlotOptions) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    plt.fill(&WHITE)?;
+
+    let total_generation = tracker.total_generation.max(1);
+
+    let (min_y, max_y) = options.series.iter()
+        .flat_map(|series| series.values(tracker).iter())
+        .fold((f64::MAX, f64::MIN), |(min_y, max_y), value| (min_y.min(*value), max_y.max(*value)));
+
+    let (min_y, max_y) = if min_y > max_y { (0.0, 1.0) } else { (min_y, max_y) };
+    let margin = ((max_y - min_y) * 0.05).max(f64::EPSILON);
+
+    let title = options.title.clone().unwrap_or(
+        format!("Model Fitness Changes (local_datetime: {:?})", tracker.local_datetime)
+    );
+
+    let mut chart = ChartBuilder::on(&plt)
+        .caption(title, ("sans-serif", 20))
+        .set_label_area_size(LabelAreaPosition::Left, 80)
+        .set_label_area_size(LabelAreaPosition::Right, 80)
+        .set_label_area_size(LabelAreaPosition::Top, 30)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .margin(10)
+        .build_cartesian_2d(0..total_generation, (min_y - margin)..(max_y + margin))?;
+
+    chart.configure_mesh()
+        .x_desc("generation")
+        .y_desc("fitness")
+        .draw()?;
+
+    for series in &options.series {
+        let color = series.color();
+
+        chart.draw_series(LineSeries::new(
+            series.values(tracker).iter().enumerate().map(|(x, y)| (x as i32, *y)),
+            &color,
+        ))?
+        .label(series.to_string())
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    chart.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    plt.present()?;
+
+    Ok(())
+}
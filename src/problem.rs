@@ -0,0 +1,465 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::fmt::Debug;
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+use super::model::Gene;
+use super::metric::FitnessMetric;
+use super::constraint::{Constraints, Violation, item_counts};
+use super::dataset::total_value;
+
+// A problem owns the genome domain, the random initializer and the fitness.
+// Lower fitness is better. Problems are serialized into checkpoints.
+pub trait Problem: Clone + Debug + Send + Sync + Serialize + DeserializeOwned + 'static {
+    // Number of loci in a gene
+    fn gene_data_len(&self) -> usize;
+
+    // Alleles are drawn from min_value..max_value
+    fn allele_range(&self) -> (i32, i32);
+
+    fn init(&self, gene: &mut Gene, rng: &mut ChaCha8Rng) {
+        let (min_value, max_value) = self.allele_range();
+        gene.init(self.gene_data_len(), min_value, max_value, rng);
+    }
+
+    fn fitness(&self, data: &[i32]) -> f64;
+
+    // Sets `gene.fitness`. Problems that cache state in `gene.allocation`
+    // can update it from `gene.changes` instead of evaluating everything.
+    fn evaluate(&self, gene: &mut Gene) {
+        gene.fitness = self.fitness(&gene.data);
+        gene.changes.clear();
+    }
+
+    // Allele that is furthest below its target, used by directed mutation
+    fn most_under_allocated(&self, _data: &[i32]) -> Option<i32> {
+        None
+    }
+
+    // Objectives of an evaluated gene for `ObjectiveMode::Nsga2`, all of them
+    // lower is better
+    fn objectives(&self, gene: &Gene) -> Vec<f64> {
+        vec![gene.fitness]
+    }
+
+    fn objective_names(&self) -> Vec<String> {
+        vec!["fitness".to_string()]
+    }
+
+    // Fixes a gene made by init, crossover or mutation so that it meets the
+    // hard constraints of the problem
+    fn repair(&self, _gene: &mut Gene, _rng: &mut ChaCha8Rng) {}
+
+    // Checked by `GAModel::validate`
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    // Constraints a gene breaks, one readable line each
+    fn violations(&self, _data: &[i32]) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// Property distribution problem
+//  - divide: target share of each heir
+//  - properties: value of each item
+//  - gene data: heir index of each item
+//  - heir_names, item_names: optional names, by position
+//  - metric: how the fitness measures the share deviation
+//  - constraints: pinned items, forbidden heirs and item count limits
+// The total value is recomputed on deserialization, since a saved one can be
+// out of date.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "PropertyDistributionFields")]
+pub struct PropertyDistribution {
+    pub divide: Vec<f64>,
+    pub properties: Vec<i32>,
+    pub heir_names: Vec<String>,
+    pub item_names: Vec<String>,
+    #[serde(default)]
+    pub metric: FitnessMetric,
+    #[serde(default)]
+    pub constraints: Constraints,
+    #[serde(skip)]
+    properties_sum: i32,
+}
+
+#[derive(Deserialize)]
+struct PropertyDistributionFields {
+    divide: Vec<f64>,
+    properties: Vec<i32>,
+    heir_names: Vec<String>,
+    item_names: Vec<String>,
+    #[serde(default)]
+    metric: FitnessMetric,
+    #[serde(default)]
+    constraints: Constraints,
+}
+
+impl From<PropertyDistributionFields> for PropertyDistribution {
+    fn from(fields: PropertyDistributionFields) -> Self {
+        let mut problem = PropertyDistribution::new(fields.divide, fields.properties)
+            .with_names(fields.heir_names, fields.item_names)
+            .with_constraints(fields.constraints);
+        problem.metric = fields.metric;
+        problem
+    }
+}
+
+impl PropertyDistribution {
+    // A total past i32::MAX is kept at i32::MAX and refused by `validate`
+    pub fn new(divide: Vec<f64>, properties: Vec<i32>) -> Self {
+        let properties_sum = total_value(&properties).unwrap_or(i32::MAX);
+        Self { divide, properties, heir_names: Vec::new(), item_names: Vec::new(), metric: FitnessMetric::default(), constraints: Constraints::default(), properties_sum }
+    }
+
+    pub fn with_names(mut self, heir_names: Vec<String>, item_names: Vec<String>) -> Self {
+        self.heir_names = heir_names;
+        self.item_names = item_names;
+        self
+    }
+
+    pub fn heir_name(&self, heir: usize) -> String {
+        self.heir_names.get(heir).cloned().unwrap_or(format!("heir {}", heir))
+    }
+
+    pub fn item_name(&self, item: usize) -> String {
+        self.item_names.get(item).cloned().unwrap_or(format!("item {}", item))
+    }
+
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    pub fn describe(&self, violation: &Violation) -> String {
+        match violation {
+            Violation::Pinned { item, heir, actual } => {
+                format!("{} must go to {} (goes to {})", self.item_name(*item), self.heir_name(*heir), self.heir_name(*actual))
+            }
+            Violation::Forbidden { item, heir } => {
+                format!("{} must not go to {}", self.item_name(*item), self.heir_name(*heir))
+            }
+            Violation::TooFewItems { heir, count, min } => {
+                format!("{} gets {} items (at least {})", self.heir_name(*heir), count, min)
+            }
+            Violation::TooManyItems { heir, count, max } => {
+                format!("{} gets {} items (at most {})", self.heir_name(*heir), count, max)
+            }
+        }
+    }
+
+    fn allocation_fitness(&self, data_divide: &[i32]) -> f64 {
+        self.metric.fitness(&self.divide, data_divide, self.properties_sum)
+    }
+
+    // `constraints.penalty` times the worst fitness of the metric per unit
+    // of violation, so that a full penalty outweighs any share deviation
+    fn penalty(&self, data: &[i32]) -> f64 {
+        let amount = self.constraints.violation_amount(data, self.divide.len());
+
+        if amount == 0 {
+            return 0.0;
+        }
+
+        self.constraints.penalty * self.metric.worst(&self.divide, self.properties_sum) * amount as f64
+    }
+
+    // Share deviation of the gene in the unit of `metric.real_name()`,
+    // without the penalty of its violations
+    pub fn real_fitness(&self, data: &[i32]) -> f64 {
+        self.metric.real_value(self.allocation_fitness(&self.allocation(data)), self.properties_sum)
+    }
+
+    // (item name, heir name) of every locus
+    pub fn assignment(&self, data: &[i32]) -> Vec<(String, String)> {
+        data.iter()
+            .enumerate()
+            .map(|(item, heir)| (self.item_name(item), self.heir_name(*heir as usize)))
+            .collect()
+    }
+
+    // Computed once in `new`
+    pub fn properties_sum(&self) -> i32 {
+        self.properties_sum
+    }
+
+    // Total value given to each heir
+    pub fn allocation(&self, data: &[i32]) -> Vec<i32> {
+        let mut data_divide: Vec<i32> = vec![0; self.divide.len()];
+
+        for (heir, value) in data.iter().zip(self.properties.iter()) {
+            data_divide[*heir as usize] += value;
+        }
+
+        data_divide
+    }
+
+    // What each heir gets from a gene
+    pub fn breakdown(&self, data: &[i32]) -> Vec<HeirAllocation> {
+        let properties_sum = self.properties_sum as f64;
+
+        let mut breakdown: Vec<HeirAllocation> = self.divide.iter().enumerate().map(|(heir, share)| HeirAllocation {
+            heir,
+            target_share: *share,
+            share: 0.0,
+            value: 0,
+            deviation: 0.0,
+            items: Vec::new(),
+        }).collect();
+
+        // Alleles out of range (a gene of another problem) are left out
+        for (item, (heir, value)) in data.iter().zip(self.properties.iter()).enumerate() {
+            if let Some(allocation) = breakdown.get_mut(*heir as usize) {
+                allocation.value += value;
+                allocation.items.push(item);
+            }
+        }
+
+        for allocation in breakdown.iter_mut() {
+            allocation.share = (allocation.value as f64) / properties_sum;
+            allocation.deviation = allocation.share - allocation.target_share;
+        }
+
+        breakdown
+    }
+}
+
+// Allocation of one heir
+//  - share: achieved share of the total value
+//  - deviation: share - target_share
+//  - items: indices of the properties given to the heir
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeirAllocation {
+    pub heir: usize,
+    pub target_share: f64,
+    pub share: f64,
+    pub value: i32,
+    pub deviation: f64,
+    pub items: Vec<usize>,
+}
+
+impl Problem for PropertyDistribution {
+    fn gene_data_len(&self) -> usize {
+        self.properties.len()
+    }
+
+    fn allele_range(&self) -> (i32, i32) {
+        (0, self.divide.len() as i32)
+    }
+
+    fn fitness(&self, data: &[i32]) -> f64 {
+        self.allocation_fitness(&self.allocation(data)) + self.penalty(data)
+    }
+
+    // O(changes + heirs) once the gene has a cached allocation
+    fn evaluate(&self, gene: &mut Gene) {
+        if gene.allocation.len() == self.divide.len() {
+            for (index, old_heir, new_heir) in gene.changes.iter() {
+                let value = self.properties[*index];
+                gene.allocation[*old_heir as usize] -= value;
+                gene.allocation[*new_heir as usize] += value;
+            }
+        } else {
+            gene.allocation = self.allocation(&gene.data);
+        }
+
+        gene.changes.clear();
+        gene.fitness = self.allocation_fitness(&gene.allocation) + self.penalty(&gene.data);
+    }
+
+    //  - value_deviation: the fitness (by `metric`)
+    //  - item_count_deviation: sum of |items of the heir / items - share|
+    //  - max_share_error: largest |achieved share - share| of a heir
+    fn objectives(&self, gene: &Gene) -> Vec<f64> {
+        let properties_sum = self.properties_sum as f64;
+        let item_len = gene.data.len().max(1) as f64;
+
+        let allocation = match gene.allocation.len() == self.divide.len() {
+            true => gene.allocation.clone(),
+            false => self.allocation(&gene.data),
+        };
+
+        let item_counts = item_counts(&gene.data, self.divide.len());
+
+        let item_count_deviation = self.divide.iter()
+            .zip(item_counts.iter())
+            .map(|(share, count)| ((*count as f64) / item_len - share).abs())
+            .sum();
+
+        let max_share_error = self.divide.iter()
+            .zip(allocation.iter())
+            .map(|(share, value)| ((*value as f64) / properties_sum - share).abs())
+            .fold(0.0, f64::max);
+
+        vec![gene.fitness, item_count_deviation, max_share_error]
+    }
+
+    fn objective_names(&self) -> Vec<String> {
+        ["value_deviation", "item_count_deviation", "max_share_error"].iter().map(|name| name.to_string()).collect()
+    }
+
+    fn repair(&self, gene: &mut Gene, rng: &mut ChaCha8Rng) {
+        self.constraints.repair(gene, self.divide.len(), rng);
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match total_value(&self.properties) {
+            None => return Err(format!("property values sum to more than {}", i32::MAX)),
+            Some(0) => return Err("property values sum to 0".to_string()),
+            // `properties` was changed after `new`
+            Some(total) if total != self.properties_sum => {
+                return Err(format!("property values sum to {} but the problem was built for {}", total, self.properties_sum));
+            }
+            Some(_total) => {}
+        }
+
+        if let Some(share) = self.divide.iter().find(|share| !share.is_finite()) {
+            return Err(format!("share is not a finite number ({})", share));
+        }
+
+        self.constraints.validate(self.divide.len(), self.properties.len())?;
+
+        // Every rule broken at once, each limit by every item
+        let amount = self.constraints.pinned.len() + self.constraints.forbidden.len() + self.constraints.item_limits.len() * self.properties.len();
+        let penalty = self.constraints.penalty * self.metric.worst(&self.divide, self.properties_sum) * amount as f64;
+
+        if !penalty.is_finite() {
+            return Err(format!("constraint penalty is too large ({:e})", self.constraints.penalty));
+        }
+
+        Ok(())
+    }
+
+    fn violations(&self, data: &[i32]) -> Vec<String> {
+        self.constraints.violations(data, self.divide.len())
+            .iter()
+            .map(|violation| self.describe(violation))
+            .collect()
+    }
+
+    fn most_under_allocated(&self, data: &[i32]) -> Option<i32> {
+        let properties_sum = self.properties_sum() as f64;
+        let data_divide = self.allocation(data);
+
+        self.divide.iter()
+            .zip(data_divide.iter())
+            .map(|(share, value)| share * properties_sum - (*value as f64))
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(heir, _gap)| heir as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::constraint::{Assignment, ConstraintHandling, ItemLimit};
+    use crate::crossover::{CrossoverOperator, OnePoint, TwoPoint, KPoint, Uniform};
+    use crate::mutation::{MutationOperator, RandomReset, Bernoulli, Swap, MoveToUnderAllocated};
+
+    // 4 heirs and 60 items, with a rule of every kind
+    fn problem(metric: FitnessMetric, handling: ConstraintHandling) -> PropertyDistribution {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let properties = (0..60).map(|_n| rng.gen_range(1..1000)).collect();
+
+        let mut problem = PropertyDistribution::new(vec![0.4, 0.3, 0.2, 0.1], properties).with_constraints(Constraints {
+            pinned: vec![Assignment { item: 0, heir: 0 }],
+            forbidden: vec![Assignment { item: 1, heir: 2 }],
+            item_limits: vec![ItemLimit { heir: 3, min: Some(5), max: Some(20) }],
+            handling,
+            penalty: 1.0,
+        });
+        problem.metric = metric;
+        problem
+    }
+
+    // Returns whether the cached allocation was updated from the changes
+    fn assert_evaluation(problem: &PropertyDistribution, gene: &mut Gene) -> bool {
+        let is_delta = !gene.allocation.is_empty();
+
+        problem.evaluate(gene);
+
+        assert_eq!(gene.allocation, problem.allocation(&gene.data));
+        assert!((gene.fitness - problem.fitness(&gene.data)).abs() < 1e-9, "{} != {}", gene.fitness, problem.fitness(&gene.data));
+
+        is_delta
+    }
+
+    #[test]
+    fn delta_evaluation_matches_fitness() {
+        let crossovers: Vec<Box<dyn CrossoverOperator>> = vec![
+            Box::new(OnePoint),
+            Box::new(TwoPoint),
+            Box::new(KPoint { k: 3 }),
+            Box::new(Uniform { bias: 0.9 }),
+        ];
+        let mutations: Vec<Box<dyn MutationOperator<PropertyDistribution>>> = vec![
+            Box::new(RandomReset { count: 2 }),
+            Box::new(Bernoulli { rate: 0.05 }),
+            Box::new(Swap { count: 2 }),
+            Box::new(MoveToUnderAllocated { count: 2 }),
+        ];
+
+        for metric in FitnessMetric::ALL {
+            for handling in ConstraintHandling::ALL {
+                let problem = problem(metric, handling);
+                let mut rng = ChaCha8Rng::seed_from_u64(1);
+                let mut deltas = 0;
+
+                let mut genes: Vec<Gene> = (0..10).map(|_n| {
+                    let mut gene = Gene::default();
+                    problem.init(&mut gene, &mut rng);
+                    problem.evaluate(&mut gene);
+                    gene
+                }).collect();
+
+                for n in 0..200 {
+                    let parent1 = &genes[rng.gen_range(0..genes.len())];
+                    let parent2 = &genes[rng.gen_range(0..genes.len())];
+                    let (mut child, _child) = crossovers[n % crossovers.len()].crossover(parent1, parent2, &mut rng);
+                    deltas += assert_evaluation(&problem, &mut child) as usize;
+
+                    mutations[n % mutations.len()].mutate(&mut child, &problem, &mut rng);
+                    deltas += assert_evaluation(&problem, &mut child) as usize;
+
+                    problem.repair(&mut child, &mut rng);
+                    deltas += assert_evaluation(&problem, &mut child) as usize;
+
+                    genes[n % 10] = child;
+                }
+
+                assert!(deltas > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn deserialization_recomputes_the_total() {
+        let problem = PropertyDistribution::new(vec![0.5, 0.5], vec![10, 20, 30]);
+        let mut json = serde_json::to_value(&problem).unwrap();
+        json["properties"] = serde_json::json!([1, 2, 3]);
+
+        let problem: PropertyDistribution = serde_json::from_value(json).unwrap();
+        assert_eq!(problem.properties_sum(), 6);
+    }
+
+    #[test]
+    fn validate_refuses_changed_properties() {
+        let mut problem = PropertyDistribution::new(vec![1.0], vec![5]);
+        assert!(problem.validate().is_ok());
+
+        problem.properties = vec![5, 6];
+        assert!(problem.validate().is_err());
+    }
+}
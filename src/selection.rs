@@ -0,0 +1,162 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use rand::prelude::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+
+use super::model::Gene;
+
+// Picks a parent from a population sorted by fitness (best first).
+// Strategies are built once per generation, so any table they need is
+// computed in the constructor and `select` stays cheap.
+pub trait SelectionStrategy: Sync {
+    fn select(&self, rng: &mut ChaCha8Rng) -> usize;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SelectionKind {
+    Truncation,
+    Tournament,
+    Roulette,
+    Rank,
+}
+
+named_enum!(SelectionKind, "selection strategy", {
+    Truncation => "truncation",
+    Tournament => "tournament",
+    Roulette => "roulette",
+    Rank => "rank",
+});
+
+// Uniform pick among the best `total_len` genes
+pub struct Truncation {
+    pub total_len: usize,
+}
+
+impl SelectionStrategy for Truncation {
+    fn select(&self, rng: &mut ChaCha8Rng) -> usize {
+        rng.gen_range(0..self.total_len)
+    }
+}
+
+// Best of `k` uniform picks. The population is sorted, so the best
+// competitor is the one with the lowest index.
+pub struct Tournament {
+    pub k: usize,
+    pub len: usize,
+}
+
+impl SelectionStrategy for Tournament {
+    fn select(&self, rng: &mut ChaCha8Rng) -> usize {
+        (0..self.k.max(1))
+            .map(|_n| rng.gen_range(0..self.len))
+            .min()
+            .unwrap()
+    }
+}
+
+// Draws an index from cumulative weights
+pub struct Weighted {
+    cumulative: Vec<f64>,
+}
+
+impl Weighted {
+    // Uniform when the weights do not add up to a finite positive total
+    // (every gene has the same fitness, or an infinite one)
+    fn new(weights: impl Iterator<Item = f64>) -> Self {
+        let mut weights: Vec<f64> = weights.collect();
+        let total: f64 = weights.iter().sum();
+
+        if !(total.is_finite() && total > 0.0) {
+            weights.fill(1.0);
+        }
+
+        let mut total = 0.0;
+        let cumulative = weights.into_iter().map(|weight| {
+            total += weight;
+            total
+        }).collect();
+
+        Self { cumulative }
+    }
+
+    // Fitness proportional. Lower fitness is better, so each gene is
+    // weighted by its distance to the worst gene of the generation.
+    pub fn roulette(genes: &[Gene]) -> Self {
+        let worst = genes.iter().map(|gene| gene.fitness).fold(f64::MIN, f64::max);
+        Self::new(genes.iter().map(|gene| worst - gene.fitness))
+    }
+
+    // Linear ranking with selection pressure in 1.0..=2.0
+    pub fn rank(len: usize, pressure: f64) -> Self {
+        let pressure = pressure.clamp(1.0, 2.0);
+        let last = (len.max(2) - 1) as f64;
+
+        Self::new((0..len).map(|rank| {
+            pressure - (2.0 * pressure - 2.0) * (rank as f64) / last
+        }))
+    }
+
+    fn total(&self) -> f64 {
+        *self.cumulative.last().unwrap_or(&0.0)
+    }
+}
+
+impl SelectionStrategy for Weighted {
+    fn select(&self, rng: &mut ChaCha8Rng) -> usize {
+        let dice = rng.gen_range(0.0..self.total());
+        let index = self.cumulative.partition_point(|value| *value <= dice);
+
+        index.min(self.cumulative.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::SeedableRng;
+
+    use super::*;
+
+    fn genes(fitnesses: &[f64]) -> Vec<Gene> {
+        fitnesses.iter().map(|fitness| Gene { fitness: *fitness, ..Gene::default() }).collect()
+    }
+
+    #[test]
+    fn weighted_falls_back_to_uniform() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let strategies = [
+            Weighted::roulette(&genes(&[0.5, 0.5, 0.5])),
+            Weighted::roulette(&genes(&[0.0, f64::INFINITY, 1.0])),
+            Weighted::roulette(&genes(&[0.0, f64::NAN, 1.0])),
+            Weighted::rank(3, f64::NAN),
+        ];
+
+        for strategy in strategies {
+            let mut counts = [0; 3];
+
+            for _n in 0..3000 {
+                counts[strategy.select(&mut rng)] += 1;
+            }
+
+            assert!(counts.iter().all(|count| *count > 800), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn roulette_prefers_lower_fitness() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let roulette = Weighted::roulette(&genes(&[0.0, 1.0, 2.0]));
+        let mut counts = [0; 3];
+
+        for _n in 0..3000 {
+            counts[roulette.select(&mut rng)] += 1;
+        }
+
+        // Weights 2, 1 and 0
+        assert!(counts[0] > counts[1] && counts[2] == 0, "{:?}", counts);
+    }
+}
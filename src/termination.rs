@@ -0,0 +1,168 @@
+// 유전자 알고리즘 -- 재산 분배 문제
+// 2022-09-01
+// 
+// YeJun Jung (yejun614@naver.com)
+
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use super::model::GAModelTracker;
+
+// Conditions that end a run before its generation count. Every condition
+// that is set is checked after each generation and the first one met wins.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Termination {
+    // Best fitness at or below this value
+    pub target_fitness: Option<f64>,
+    // Generations without a better best gene
+    pub stagnation_limit: Option<usize>,
+    pub time_limit_seconds: Option<f64>,
+    pub max_evaluations: Option<u64>,
+    // Diversity of the last generation at or below this value
+    pub min_diversity: Option<f64>,
+}
+
+impl Termination {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(time_limit_seconds) = self.time_limit_seconds {
+            if time_limit_seconds.is_nan() || time_limit_seconds <= 0.0 {
+                return Err(format!("time_limit_seconds must be positive ({})", time_limit_seconds));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn check(&self, tracker: &GAModelTracker) -> Option<StopReason> {
+        if let Some(target_fitness) = self.target_fitness {
+            if tracker.best_gene.fitness <= target_fitness {
+                return Some(StopReason::TargetFitness);
+            }
+        }
+
+        if let Some(stagnation_limit) = self.stagnation_limit {
+            if tracker.stagnation() >= stagnation_limit {
+                return Some(StopReason::Stagnation);
+            }
+        }
+
+        if let Some(time_limit_seconds) = self.time_limit_seconds {
+            if tracker.elapsed.as_secs_f64() >= time_limit_seconds {
+                return Some(StopReason::TimeLimit);
+            }
+        }
+
+        if let Some(max_evaluations) = self.max_evaluations {
+            if tracker.evaluations >= max_evaluations {
+                return Some(StopReason::MaxEvaluations);
+            }
+        }
+
+        if let (Some(min_diversity), Some(diversity)) = (self.min_diversity, tracker.average_diff_changes.last()) {
+            if *diversity <= min_diversity {
+                return Some(StopReason::DiversityCollapse);
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StopReason {
+    Generations,
+    TargetFitness,
+    Stagnation,
+    TimeLimit,
+    MaxEvaluations,
+    DiversityCollapse,
+    Stopped,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            StopReason::Generations => "generations",
+            StopReason::TargetFitness => "target-fitness",
+            StopReason::Stagnation => "stagnation",
+            StopReason::TimeLimit => "time-limit",
+            StopReason::MaxEvaluations => "max-evaluations",
+            StopReason::DiversityCollapse => "diversity-collapse",
+            StopReason::Stopped => "stopped",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // 5 generations, the best gene found by the second one
+    fn tracker() -> GAModelTracker {
+        let mut tracker = GAModelTracker {
+            best_generation: 1,
+            elapsed: Duration::from_secs(2),
+            evaluations: 500,
+            ..GAModelTracker::default()
+        };
+        tracker.best_gene.fitness = 10.0;
+
+        for diff in [4.0, 3.0, 2.0, 1.0, 0.5] {
+            tracker.append(10.0, 20.0, diff);
+        }
+
+        tracker
+    }
+
+    #[test]
+    fn check_meets_each_condition_at_its_limit() {
+        let tracker = tracker();
+        assert_eq!(tracker.stagnation(), 3);
+
+        let cases = [
+            (Termination { target_fitness: Some(10.0), ..Termination::default() }, StopReason::TargetFitness),
+            (Termination { stagnation_limit: Some(3), ..Termination::default() }, StopReason::Stagnation),
+            (Termination { time_limit_seconds: Some(2.0), ..Termination::default() }, StopReason::TimeLimit),
+            (Termination { max_evaluations: Some(500), ..Termination::default() }, StopReason::MaxEvaluations),
+            (Termination { min_diversity: Some(0.5), ..Termination::default() }, StopReason::DiversityCollapse),
+        ];
+
+        for (termination, stop_reason) in cases {
+            assert_eq!(termination.check(&tracker), Some(stop_reason));
+        }
+
+        let unmet = [
+            Termination::default(),
+            Termination { target_fitness: Some(9.9), ..Termination::default() },
+            Termination { stagnation_limit: Some(4), ..Termination::default() },
+            Termination { time_limit_seconds: Some(2.5), ..Termination::default() },
+            Termination { max_evaluations: Some(501), ..Termination::default() },
+            Termination { min_diversity: Some(0.4), ..Termination::default() },
+        ];
+
+        for termination in unmet {
+            assert_eq!(termination.check(&tracker), None);
+        }
+    }
+
+    #[test]
+    fn check_reports_the_first_condition_met() {
+        let termination = Termination {
+            stagnation_limit: Some(1),
+            max_evaluations: Some(1),
+            min_diversity: Some(1.0),
+            ..Termination::default()
+        };
+        assert_eq!(termination.check(&tracker()), Some(StopReason::Stagnation));
+
+        // No generation has a diversity yet
+        let termination = Termination { min_diversity: Some(1.0), ..Termination::default() };
+        assert_eq!(termination.check(&GAModelTracker::default()), None);
+    }
+}